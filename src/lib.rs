@@ -39,6 +39,20 @@
 //!     println!("{}, {}", point.x, point.y);
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! * `serde`: Derives `Serialize` and `Deserialize` for the generators, so a
+//!   partially-completed generator can be saved and resumed later. This needs
+//!   an rng that is also serializable, such as `Pcg64Mcg` with its `serde1`
+//!   feature enabled.
+//!
+//!   Closures can't be serialized, so they are left out and need attaching
+//!   again after deserializing. Use `set_density` to restore the density
+//!   function, since `with_density` would clear the grid, and
+//!   `with_stop_condition` to restore the stop condition. Checkpoints are
+//!   kept, but need `with_checkpoints` calling again with the same interval
+//!   before any more are recorded.
 
 #![deny(
     dead_code,
@@ -49,15 +63,298 @@
     trivial_numeric_casts
 )]
 
+use std::cell::Cell;
 use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use std::fmt;
+use std::iter;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use glam::Vec2;
 use itertools::Itertools;
 use rand::Rng;
 use rand::SeedableRng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A source of time, used to bound how long generation may run.
+///
+/// The real implementation is `SystemClock`. `MockClock` can be
+/// used to drive the time-budgeted functions deterministically.
+pub trait Clock {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A `Clock` that reads the system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A `Clock` that moves forward by a fixed step each time it
+/// is read, and can be advanced manually.
+///
+/// ```
+/// use std::time::Duration;
+/// use bluenoise::{Clock, MockClock};
+///
+/// let clock = MockClock::new(Duration::from_millis(1));
+/// let start = clock.now();
+/// clock.advance(Duration::from_millis(5));
+/// assert_eq!(clock.now() - start, Duration::from_millis(6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Cell<Instant>,
+    step: Duration,
+}
+
+impl MockClock {
+    /// Creates a new `MockClock`.
+    ///
+    /// * `step`: How far the clock advances every time it is read.
+    pub fn new(step: Duration) -> Self {
+        Self {
+            now: Cell::new(Instant::now()),
+            step,
+        }
+    }
+
+    /// Move the clock forward by some duration.
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        let now = self.now.get();
+        self.advance(self.step);
+        now
+    }
+}
+
+/// The splitmix64 mixing function, which scrambles the bits of
+/// a value into something that looks random.
+fn splitmix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Get the centre of the circle through three points, if
+/// they aren't all on a line.
+fn circumcenter(a: Vec2, b: Vec2, c: Vec2) -> Option<Vec2> {
+    let (b, c) = (b - a, c - a);
+    let d = 2.0 * (b.x * c.y - b.y * c.x);
+    if d.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let (b2, c2) = (b.length_squared(), c.length_squared());
+    Some(a + Vec2::new(c.y * b2 - b.y * c2, b.x * c2 - c.x * b2) / d)
+}
+
+/// Pull points from an iterator until it is exhausted or
+/// the budget has elapsed on the given clock.
+fn take_for<I, C>(noise: &mut I, budget: Duration, clock: &C) -> Vec<Vec2>
+where
+    I: Iterator<Item = Vec2>,
+    C: Clock,
+{
+    let start = clock.now();
+    let mut points = Vec::new();
+    while clock.now().saturating_duration_since(start) < budget {
+        match noise.next() {
+            Some(point) => points.push(point),
+            None => break,
+        }
+    }
+    points
+}
+
+/// The number of samples to try around each point, unless set
+/// with `with_samples`.
+const DEFAULT_SAMPLES: u32 = 4;
+
+/// The number of times to try placing the starting point at random
+/// before searching for one, in case most of the box is blocked.
+const INITIAL_ATTEMPTS: usize = 64;
+
+/// A grid of distances to the nearest obstacle, covering the box.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct DistanceTransform {
+    grid: Vec<f32>,
+    cols: usize,
+    rows: usize,
+    min_clearance: f32,
+    /// An optional factor from clearance to local radius.
+    radius_scale: Option<f32>,
+}
+
+/// A set of circles to keep out of, bucketed into a coarse grid
+/// over the box so only the nearby ones are checked.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Exclusions {
+    circles: Vec<(Vec2, f32)>,
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Exclusions {
+    fn new(circles: Vec<(Vec2, f32)>, width: f32, height: f32, cell_size: f32) -> Self {
+        let cols = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+
+        let mut exclusions = Self {
+            circles,
+            cell_size,
+            cols,
+            rows,
+            buckets: vec![Vec::new(); cols * rows],
+        };
+        for (index, (center, radius)) in exclusions.circles.iter().enumerate() {
+            let (min_x, min_y) = exclusions.cell(*center - Vec2::new(*radius, *radius));
+            let (max_x, max_y) = exclusions.cell(*center + Vec2::new(*radius, *radius));
+            for (x, y) in (min_x..=max_x).cartesian_product(min_y..=max_y) {
+                exclusions.buckets[y * cols + x].push(index);
+            }
+        }
+        exclusions
+    }
+
+    /// Get the cell a position falls in, clamped to the grid.
+    fn cell(&self, point: Vec2) -> (usize, usize) {
+        let x = (point.x / self.cell_size).max(0.0) as usize;
+        let y = (point.y / self.cell_size).max(0.0) as usize;
+        (x.min(self.cols - 1), y.min(self.rows - 1))
+    }
+
+    /// The number of bytes the circles and buckets hold on the heap.
+    fn memory_usage(&self) -> usize {
+        self.circles.capacity() * std::mem::size_of::<(Vec2, f32)>()
+            + self.buckets.capacity() * std::mem::size_of::<Vec<usize>>()
+            + self
+                .buckets
+                .iter()
+                .map(|bucket| bucket.capacity() * std::mem::size_of::<usize>())
+                .sum::<usize>()
+    }
+
+    /// Check whether a position is strictly inside any of the circles.
+    fn contains(&self, point: Vec2) -> bool {
+        let (x, y) = self.cell(point);
+        self.buckets[y * self.cols + x].iter().any(|index| {
+            let (center, radius) = self.circles[*index];
+            point.distance_squared(center) < radius * radius
+        })
+    }
+}
+
+/// A function giving the minimum radius around a position.
+#[derive(Clone)]
+struct Density(Arc<dyn Fn(Vec2) -> f32 + Send + Sync>);
+
+impl fmt::Debug for Density {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Density(..)")
+    }
+}
+
+/// A function polled before each point to decide whether to stop.
+/// Clones share the same function, and any state it captures.
+#[derive(Clone)]
+struct StopCondition(Arc<Mutex<dyn FnMut() -> bool + Send>>);
+
+impl fmt::Debug for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StopCondition(..)")
+    }
+}
+
+/// The parts of a generator's state that change as it runs.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Snapshot<R> {
+    milestone: usize,
+    grid: Vec<Option<Vec2>>,
+    active_points: Vec<Vec2>,
+    rng: R,
+    init: bool,
+    emitted: usize,
+    yielded: usize,
+    max_radius: f32,
+    band_cursor: usize,
+    pending: Vec<Vec2>,
+}
+
+impl<R> Snapshot<R> {
+    /// The number of bytes the snapshot holds on the heap.
+    fn memory_usage(&self) -> usize {
+        self.grid.capacity() * std::mem::size_of::<Option<Vec2>>()
+            + (self.active_points.capacity() + self.pending.capacity())
+                * std::mem::size_of::<Vec2>()
+    }
+}
+
+/// Takes a snapshot of a generator.
+type SnapshotFn<R> = fn(&BlueNoise<R>) -> Snapshot<R>;
+
+/// A single step of the generator, as produced by `steps_detailed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepDetail {
+    /// A candidate point was checked against the points so far.
+    Candidate {
+        /// The position of the candidate.
+        point: Vec2,
+        /// Whether the candidate was far enough from the other points,
+        /// and was added.
+        accepted: bool,
+        /// The point the candidate was generated around. This is `None`
+        /// for the starting point and for reflections.
+        parent: Option<Vec2>,
+    },
+    /// An active point ran out of samples, so no more candidates
+    /// will be generated around it.
+    Retired {
+        /// The position of the retired point.
+        point: Vec2,
+    },
+}
+
+/// How far `steps_detailed` is through the current step.
+#[derive(Debug, Default)]
+struct DetailState {
+    /// The index, position, offset and next sample of the
+    /// active point being sampled around.
+    attempt: Option<(usize, Vec2, f32, u32)>,
+    /// The number of samples tried in the current gap.
+    band_sample: u32,
+}
+
+/// An axis through the centre of the box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Axis {
+    /// The vertical line through the centre, mirroring left to right.
+    Vertical,
+    /// The horizontal line through the centre, mirroring top to bottom.
+    Horizontal,
+}
 
 /// Provides a source of `BlueNoise` in a given area at some density.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BlueNoise<R: Rng> {
     width: f32,
     height: f32,
@@ -76,8 +373,60 @@ pub struct BlueNoise<R: Rng> {
     /// points around.
     active_points: Vec<Vec2>,
 
+    /// An optional number of evenly spaced angles to try around
+    /// each point, instead of `max_samples` random ones.
+    fixed_angles: Option<u32>,
+
+    /// An optional map of clearance, where points may only
+    /// be placed far enough from obstacles.
+    distance_transform: Option<DistanceTransform>,
+
+    /// An optional set of circles that points may not be placed in.
+    exclusions: Option<Exclusions>,
+
     rng: R,
     init: bool,
+
+    /// The number of points placed in the grid so far.
+    emitted: usize,
+    /// The number of points returned by the iterator so far.
+    yielded: usize,
+    /// Whether any seed points have been inserted.
+    seeded: bool,
+
+    /// An optional function that varies the minimum radius
+    /// by position. The `radius` acts as its floor. This can't
+    /// be serialized, so it is restored with `set_density`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    density: Option<Density>,
+    /// The largest local radius of any point in the grid.
+    max_radius: f32,
+
+    /// An optional upper limit on the distance between a
+    /// point and its nearest neighbour.
+    max_spacing: Option<f32>,
+    /// The next cell to check for gaps larger than `max_spacing`.
+    band_cursor: usize,
+
+    /// An optional axis to mirror every point across.
+    symmetry: Option<Axis>,
+    /// Mirrored points that are waiting to be emitted.
+    pending: Vec<Vec2>,
+
+    /// An optional function that can end generation early.
+    /// Like `density`, this isn't serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stop_condition: Option<StopCondition>,
+
+    /// How many points to place between checkpoints, if enabled.
+    checkpoint_interval: Option<usize>,
+    /// The recorded checkpoints, in order of milestone.
+    checkpoints: Vec<Snapshot<R>>,
+    /// Takes a snapshot. This is set by `with_checkpoints`, which is
+    /// the only place we know the rng can be cloned, so it isn't
+    /// serialized and is restored by calling that again.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    snapshot: Option<SnapshotFn<R>>,
 }
 
 impl<R: Rng + SeedableRng> BlueNoise<R> {
@@ -110,6 +459,146 @@ impl<R: Rng + SeedableRng> BlueNoise<R> {
         self.rng = SeedableRng::seed_from_u64(seed);
         self
     }
+
+    /// Blends between the noise generated by two seeds, for animating
+    /// a transition from one layout to another. Each point from `seed_a`
+    /// is matched with the nearest unclaimed point from `seed_b` and moved
+    /// towards it by `t`. Points without a partner stay where they are,
+    /// and swap over half way through.
+    ///
+    /// At `t = 0.0` this gives exactly the points for `seed_a`, and at
+    /// `t = 1.0` exactly the points for `seed_b`, in a different order.
+    /// In between, the spacing is only approximately maintained.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `min_radius`: The minimum distance between points.
+    /// * `seed_a`: The seed for the start of the transition.
+    /// * `seed_b`: The seed for the end of the transition.
+    /// * `t`: How far through the transition to go, from 0 to 1.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// for frame in 0..=10 {
+    ///     let t = frame as f32 / 10.0;
+    ///     let points = BlueNoise::<Pcg64Mcg>::morph(50.0, 50.0, 5.0, 1, 2, t);
+    ///     println!("frame {}: {} points", frame, points.len());
+    /// }
+    /// ```
+    pub fn morph(
+        width: f32,
+        height: f32,
+        min_radius: f32,
+        seed_a: u64,
+        seed_b: u64,
+        t: f32,
+    ) -> Vec<Vec2> {
+        let from = Self::from_seed(width, height, min_radius, seed_a).collect::<Vec<_>>();
+        let to = Self::from_seed(width, height, min_radius, seed_b).collect::<Vec<_>>();
+
+        // bucket the targets so we only search nearby cells
+        let grid_width = (width / min_radius).ceil() as usize;
+        let grid_height = (height / min_radius).ceil() as usize;
+        let cell = |point: Vec2| {
+            let x = ((point.x / min_radius) as usize).min(grid_width - 1);
+            let y = ((point.y / min_radius) as usize).min(grid_height - 1);
+            (x, y)
+        };
+        let mut buckets = vec![Vec::new(); grid_width * grid_height];
+        for (index, point) in to.iter().enumerate() {
+            let (x, y) = cell(*point);
+            buckets[y * grid_width + x].push(index);
+        }
+
+        let mut claimed = vec![false; to.len()];
+        let mut points = Vec::with_capacity(from.len().max(to.len()));
+        let mut unmatched = Vec::new();
+        for start in from {
+            let (x, y) = cell(start);
+            let nearest = (x.saturating_sub(2)..(x + 3).min(grid_width))
+                .cartesian_product(y.saturating_sub(2)..(y + 3).min(grid_height))
+                .flat_map(|(x, y)| buckets[y * grid_width + x].iter().copied())
+                .filter(|index| !claimed[*index])
+                .map(|index| (index, start.distance_squared(to[index])))
+                .filter(|(_, distance)| *distance <= 4.0 * min_radius * min_radius)
+                .min_by(|a, b| a.1.partial_cmp(&b.1).expect("Distances are never NaN."));
+
+            match nearest {
+                Some((index, _)) => {
+                    claimed[index] = true;
+                    points.push(start * (1.0 - t) + to[index] * t);
+                }
+                None => unmatched.push(start),
+            }
+        }
+
+        if t < 0.5 {
+            points.extend(unmatched);
+        } else {
+            points.extend(
+                claimed
+                    .iter()
+                    .zip(to)
+                    .filter(|(claimed, _)| !**claimed)
+                    .map(|(_, point)| point),
+            );
+        }
+
+        points
+    }
+
+    /// Generates a large field as a patchwork of square patches, each
+    /// with its own seed derived from `base_seed` and its position. All
+    /// the patches share one grid, and each grows out from the points
+    /// already placed along its edges, so the spacing holds across the
+    /// boundaries and there are no visible seams or repeats. Any room
+    /// that growth can't reach, which is common when patches are small,
+    /// is found by searching the patch and filled from there.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `min_radius`: The minimum distance between points.
+    /// * `patch_size`: The width and height of each patch.
+    /// * `base_seed`: The seed to derive the seed of each patch from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `patch_size` is not larger than zero.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let points = BlueNoise::<Pcg64Mcg>::patchwork(200.0, 100.0, 1.0, 25.0, 10);
+    ///
+    /// assert!(points.iter().all(|p| p.x < 200.0 && p.y < 100.0));
+    /// ```
+    pub fn patchwork(
+        width: f32,
+        height: f32,
+        min_radius: f32,
+        patch_size: f32,
+        base_seed: u64,
+    ) -> Vec<Vec2> {
+        assert!(patch_size > 0.0, "The patch size must be larger than zero.");
+        let mut noise = Self::from_seed(width, height, min_radius, base_seed);
+        noise.init = true;
+
+        let cols = (width / patch_size).ceil() as usize;
+        let rows = (height / patch_size).ceil() as usize;
+        let mut points = Vec::new();
+        for (y, x) in (0..rows).cartesian_product(0..cols) {
+            let index = (y * cols + x) as u64;
+            noise.with_seed(splitmix(base_seed ^ splitmix(index)));
+
+            let min = Vec2::new(x as f32, y as f32) * patch_size;
+            let max = (min + Vec2::new(patch_size, patch_size)).min(Vec2::new(width, height));
+            points.extend(noise.fill_patch(min, max));
+        }
+        points
+    }
 }
 
 impl<R: Rng> BlueNoise<R> {
@@ -130,7 +619,7 @@ impl<R: Rng> BlueNoise<R> {
         Self {
             width,
             height,
-            max_samples: 4,
+            max_samples: DEFAULT_SAMPLES,
             radius: min_radius,
             radius_squared,
             cell_size,
@@ -138,11 +627,101 @@ impl<R: Rng> BlueNoise<R> {
             grid_width,
             grid_height,
             active_points: Vec::<Vec2>::default(),
+            fixed_angles: None,
+            distance_transform: None,
+            exclusions: None,
             rng,
             init: false,
+            emitted: 0,
+            yielded: 0,
+            seeded: false,
+            density: None,
+            max_radius: min_radius,
+            max_spacing: None,
+            band_cursor: 0,
+            symmetry: None,
+            pending: Vec::new(),
+            stop_condition: None,
+            checkpoint_interval: None,
+            checkpoints: Vec::new(),
+            snapshot: None,
         }
     }
 
+    /// Fills a rotated rectangle with noise. The points are generated in
+    /// an axis-aligned box of the same size and then rotated into place,
+    /// so no samples are wasted on the area outside the rectangle.
+    ///
+    /// * `center`: The centre of the rectangle.
+    /// * `half_extents`: Half of the width and height of the rectangle.
+    /// * `angle`: The anticlockwise rotation of the rectangle, in radians.
+    /// * `min_radius`: The minimum distance between points.
+    /// * `rng`: Rng to use
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand::SeedableRng;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let road = BlueNoise::oriented_rect(
+    ///     Vec2::new(50.0, 50.0),
+    ///     Vec2::new(40.0, 5.0),
+    ///     std::f32::consts::FRAC_PI_4,
+    ///     1.0,
+    ///     Pcg64Mcg::seed_from_u64(10),
+    /// );
+    ///
+    /// for point in road {
+    ///     println!("{}, {}", point.x, point.y);
+    /// }
+    /// ```
+    pub fn oriented_rect(
+        center: Vec2,
+        half_extents: Vec2,
+        angle: f32,
+        min_radius: f32,
+        rng: R,
+    ) -> Vec<Vec2> {
+        let size = half_extents * 2.0;
+        let (sin, cos) = angle.sin_cos();
+        Self::from_rng(size.x, size.y, min_radius, rng)
+            .map(|point| {
+                let local = point - half_extents;
+                center + Vec2::new(local.x * cos - local.y * sin, local.x * sin + local.y * cos)
+            })
+            .collect()
+    }
+
+    /// Generates `count` uniformly random points in the box, with no
+    /// spacing between them. This is useful as a baseline to compare
+    /// blue noise against, using the same rendering or analysis.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `count`: The number of points to generate.
+    /// * `rng`: Rng to use
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand::SeedableRng;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10).collect::<Vec<_>>();
+    /// let white = BlueNoise::uniform(50.0, 50.0, noise.len(), Pcg64Mcg::seed_from_u64(10));
+    ///
+    /// assert_eq!(noise.len(), white.len());
+    /// ```
+    pub fn uniform(width: f32, height: f32, count: usize, mut rng: R) -> Vec<Vec2> {
+        (0..count)
+            .map(|_| {
+                let x = rng.gen_range(0.0..width);
+                let y = rng.gen_range(0.0..height);
+                Vec2::new(x, y)
+            })
+            .collect()
+    }
+
     /// A builder function to set the maximum number of
     /// samples to be when attempting to find new points.
     ///
@@ -152,328 +731,3198 @@ impl<R: Rng> BlueNoise<R> {
         self
     }
 
-    /// A builder function to set the minimum radius between
-    /// points.
+    /// A builder function to only place points that are far enough from
+    /// obstacles. The distance transform is a row-major grid of `cols` by
+    /// `rows` cells stretched over the box, holding the distance from each
+    /// cell to the nearest obstacle. Candidates that fall in a cell with
+    /// less than `min_clearance` are rejected.
     ///
-    /// For an example, see the `BlueNoise` examples.
-    pub fn with_min_radius(&mut self, min_radius: f32) -> &mut Self {
-        self.radius = min_radius;
+    /// To also space points out further where there is more room, follow
+    /// this with `with_clearance_scaling`.
+    ///
+    /// If the starting point keeps landing on obstacles, the clear cells
+    /// are searched for one instead. If no cell has enough clearance,
+    /// or every clear cell is also excluded, no points are generated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cols` or `rows` is zero, or if the grid doesn't have
+    /// `cols * rows` cells.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// // an obstacle along the left edge
+    /// let clearance = (0..10 * 10).map(|i| (i % 10) as f32).collect::<Vec<_>>();
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+    /// let noise = noise.with_distance_transform(clearance, 10, 10, 2.0);
+    ///
+    /// assert!(noise.all(|point| point.x >= 10.0));
+    /// ```
+    pub fn with_distance_transform(
+        &mut self,
+        grid: Vec<f32>,
+        cols: usize,
+        rows: usize,
+        min_clearance: f32,
+    ) -> &mut Self {
+        assert!(
+            cols > 0 && rows > 0,
+            "The distance transform must have at least one cell."
+        );
+        assert_eq!(
+            grid.len(),
+            cols * rows,
+            "The distance transform must have cols * rows cells."
+        );
+        self.distance_transform = Some(DistanceTransform {
+            grid,
+            cols,
+            rows,
+            min_clearance,
+            radius_scale: None,
+        });
         self
     }
 
-    /// Resets the generator to begin creating noise from the beginning.
-    /// This will not reset the prng so if you want deterministic ordering,
-    /// make sure to set it explicitly.
+    /// A builder function to grow the minimum radius with the clearance
+    /// in the distance transform, so points are packed tightly near
+    /// obstacles and spread out where there is more room. The local
+    /// radius is `scale` times the clearance of the cell, but never less
+    /// than the minimum radius. Like with `with_density`, two points must
+    /// be at least the larger of their radii apart.
+    ///
+    /// This should be called after `with_distance_transform`, and before
+    /// generating any points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no distance transform.
     ///
     /// ```
     /// use bluenoise::BlueNoise;
     /// use rand_pcg::Pcg64Mcg;
     ///
-    /// let mut noise = BlueNoise::<Pcg64Mcg>::new(10.0, 10.0, 1.0);
-    /// let first_10 = noise.with_seed(25).take(10).collect::<Vec<_>>();
+    /// // an obstacle along the left edge
+    /// let clearance = (0..10 * 10).map(|i| (i % 10) as f32 * 5.0).collect::<Vec<_>>();
     ///
-    /// // make sure to re-initialise your seed!
-    /// noise.reset().with_seed(25);
-    /// let reset_10 = noise.take(10).collect::<Vec<_>>();
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise
+    ///     .with_distance_transform(clearance, 10, 10, 1.0)
+    ///     .with_clearance_scaling(0.2);
     ///
-    /// assert_eq!(first_10, reset_10);
+    /// for point in noise.take(10) {
+    ///     println!("{}, {}", point.x, point.y);
+    /// }
     /// ```
-    pub fn reset(&mut self) -> &mut Self {
-        self.init = false;
-        self.active_points.clear();
-        for item in &mut self.grid {
-            *item = None;
-        }
+    pub fn with_clearance_scaling(&mut self, scale: f32) -> &mut Self {
+        let transform = self
+            .distance_transform
+            .as_mut()
+            .expect("Clearance scaling needs a distance transform.");
+        transform.radius_scale = Some(scale);
         self
     }
 
-    /// Compute the distance between two points
-    fn distance(&self, point: Vec2, target: Vec2) -> f32 {
-        point.distance_squared(target)
+    /// A builder function to keep points out of a set of circles, given
+    /// as a centre and radius, such as around existing objects of
+    /// different sizes. Candidates strictly inside any circle are
+    /// rejected. The circles are bucketed up front into cells about the
+    /// size of the radius, with large circles added to every cell they
+    /// cover, so only the few near each candidate are checked.
+    ///
+    /// If the circles cover the whole box, no points are generated.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let circles = vec![(Vec2::new(10.0, 10.0), 5.0), (Vec2::new(35.0, 30.0), 12.0)];
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+    /// let noise = noise.with_exclusion_circles(circles.clone());
+    ///
+    /// assert!(noise.all(|p| circles.iter().all(|(c, r)| p.distance(*c) >= *r)));
+    /// ```
+    pub fn with_exclusion_circles(&mut self, circles: Vec<(Vec2, f32)>) -> &mut Self {
+        self.exclusions = Some(Exclusions::new(
+            circles,
+            self.width,
+            self.height,
+            self.radius,
+        ));
+        self
     }
 
-    /// Check if a position is far enough away from
-    /// nearby previously created points.
-    fn is_valid(&self, point: Vec2) -> bool {
-        // remove anything outside our box
-        if point.x < 0.0 || point.x > self.width || point.y < 0.0 || point.y > self.height {
-            return false;
-        };
-
-        let x_range = {
-            let x = (point.x / self.cell_size) as usize;
-            x.saturating_sub(2)..(x + 3).min(self.grid_width)
-        };
-        let y_range = {
-            let y = (point.y / self.cell_size) as usize;
-            y.saturating_sub(2)..(y + 3).min(self.grid_height)
-        };
+    /// A builder function to try exactly `k` evenly spaced angles around
+    /// each point, in place of `max_samples`. The angles are rotated by
+    /// an amount derived from the position of the point rather than the
+    /// rng, so the sampling is structured and reproducible.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 5.0, 10);
+    /// let noise = noise.with_fixed_angles(6);
+    ///
+    /// for point in noise.take(10) {
+    ///     println!("{}, {}", point.x, point.y);
+    /// }
+    /// ```
+    pub fn with_fixed_angles(&mut self, k: u32) -> &mut Self {
+        self.fixed_angles = Some(k);
+        self
+    }
 
-        x_range.cartesian_product(y_range).all(|(x, y)| {
-            // if there is a point, check if it is further than our min radius
-            match self
-                .grid
-                .get(y * self.grid_width + x)
-                .expect("Ended up out of bounds when fetching point.")
-            {
-                Some(target) => self.distance(point, *target) >= self.radius_squared,
-                None => true,
-            }
-        })
+    /// A builder function to set the minimum radius between
+    /// points.
+    ///
+    /// For an example, see the `BlueNoise` examples.
+    pub fn with_min_radius(&mut self, min_radius: f32) -> &mut Self {
+        self.radius = min_radius;
+        self
     }
 
-    /// Get some nearby point
-    fn get_nearby(&mut self, position: Vec2, seed: f32, sample: u32) -> Vec2 {
-        let offset = seed + sample as f32 / self.max_samples as f32;
-        let theta = 2.0 * PI * offset;
-        let radius = self.radius + 0.001;
-        Vec2::new(
-            position.x + radius * theta.cos(),
-            position.y + radius * theta.sin(),
-        )
+    /// A builder function to vary the minimum radius between points
+    /// by position. The closure returns the desired radius at a given
+    /// point, and two points must be at least the larger of their
+    /// radii apart.
+    ///
+    /// The grid is sized to `min_radius`, which is used as a floor for
+    /// whatever the closure returns. This rebuilds the grid, so it should
+    /// be called before generating any points.
+    ///
+    /// Note that the local radius isn't known ahead of time, so
+    /// `estimated_points` can no longer give a useful figure.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise.with_density(1.0, |point| 1.0 + point.x / 10.0);
+    ///
+    /// for point in noise.take(10) {
+    ///     println!("{}, {}", point.x, point.y);
+    /// }
+    /// ```
+    pub fn with_density<F>(&mut self, min_radius: f32, density: F) -> &mut Self
+    where
+        F: Fn(Vec2) -> f32 + Send + Sync + 'static,
+    {
+        self.density = Some(Density(Arc::new(density)));
+        self.resize_grid(min_radius)
     }
 
-    /// Get the index for a given position
-    fn grid_index(&self, position: Vec2) -> usize {
+    /// Replaces the function that varies the minimum radius, without
+    /// touching the grid or any points generated so far. The density
+    /// function isn't serialized, so this is how to attach it again to
+    /// a generator that was configured with `with_density` and then
+    /// deserialized. It should return the same radii as before, or the
+    /// spacing of the resumed points won't match.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let density = |point: glam::Vec2| 1.0 + point.x / 10.0;
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise.with_density(1.0, density);
+    /// noise.by_ref().take(10).for_each(drop);
+    ///
+    /// // after restoring `noise` from a save
+    /// let noise = noise.set_density(density);
+    /// assert!(noise.count() > 0);
+    /// ```
+    pub fn set_density<F>(&mut self, density: F) -> &mut Self
+    where
+        F: Fn(Vec2) -> f32 + Send + Sync + 'static,
+    {
+        self.density = Some(Density(Arc::new(density)));
+        self
+    }
+
+    /// A builder function to limit the spacing between points to a band.
+    /// No two points will be closer than `min_radius`, and once the area
+    /// has been filled, any point without a neighbour within `max_radius`
+    /// has more points inserted around it where there is room.
+    ///
+    /// It isn't always possible to fit a point into a gap, so a small
+    /// number of points may still end up further than `max_radius` from
+    /// their nearest neighbour. This rebuilds the grid, so it should be
+    /// called before generating any points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_radius` is not larger than `min_radius`.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise.with_spacing_band(1.0, 1.5);
+    ///
+    /// for point in noise.take(10) {
+    ///     println!("{}, {}", point.x, point.y);
+    /// }
+    /// ```
+    pub fn with_spacing_band(&mut self, min_radius: f32, max_radius: f32) -> &mut Self {
+        assert!(
+            max_radius > min_radius,
+            "The maximum spacing must be larger than the minimum."
+        );
+        self.max_spacing = Some(max_radius);
+        self.resize_grid(min_radius)
+    }
+
+    /// A builder function to make the noise symmetric about an axis
+    /// through the centre of the box. Points are generated in one half,
+    /// the left or the top, and each is followed by its reflection.
+    /// Candidates whose reflection would break the minimum radius,
+    /// including with the candidate itself, are rejected.
+    ///
+    /// Seed points and external candidates may be in either half. They
+    /// are placed along with their reflections, and rejected the same way.
+    ///
+    /// ```
+    /// use bluenoise::{Axis, BlueNoise};
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 5.0, 10);
+    /// let points = noise.with_mirror_symmetry(Axis::Vertical).collect::<Vec<_>>();
+    ///
+    /// for point in points.iter().filter(|p| p.x < 25.0) {
+    ///     let mirror = Vec2::new(50.0 - point.x, point.y);
+    ///     assert!(points.iter().any(|p| p.distance(mirror) < 1e-3));
+    /// }
+    /// ```
+    pub fn with_mirror_symmetry(&mut self, axis: Axis) -> &mut Self {
+        self.symmetry = Some(axis);
+        self
+    }
+
+    /// A builder function to stop generation cooperatively, for example
+    /// when the user cancels. The closure is polled once at the start of
+    /// every call to `next`, and the iterator returns `None` whenever it
+    /// returns `true`. If it later returns `false` again, generation
+    /// carries on from where it stopped.
+    ///
+    /// Cloning the generator doesn't clone the closure. Every clone polls
+    /// the same one, so a single flag can cancel them all, but any state
+    /// it keeps, like the counter below, is advanced by all of them.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut polls = 0;
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise.with_stop_condition(move || {
+    ///     polls += 1;
+    ///     polls > 10
+    /// });
+    ///
+    /// assert_eq!(noise.count(), 10);
+    /// ```
+    pub fn with_stop_condition<F>(&mut self, stop_condition: F) -> &mut Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        self.stop_condition = Some(StopCondition(Arc::new(Mutex::new(stop_condition))));
+        self
+    }
+
+    /// Rebuild the grid for a new minimum radius, and reset.
+    fn resize_grid(&mut self, min_radius: f32) -> &mut Self {
+        self.radius = min_radius;
+        self.radius_squared = min_radius * min_radius;
+        self.cell_size = min_radius * FRAC_1_SQRT_2;
+        self.grid_width = (self.width / self.cell_size).ceil() as usize;
+        self.grid_height = (self.height / self.cell_size).ceil() as usize;
+        self.grid = vec![None; self.grid_width * self.grid_height];
+        self.reset()
+    }
+
+    /// Resets the generator to begin creating noise from the beginning.
+    /// This will not reset the prng so if you want deterministic ordering,
+    /// make sure to set it explicitly.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::new(10.0, 10.0, 1.0);
+    /// let first_10 = noise.with_seed(25).take(10).collect::<Vec<_>>();
+    ///
+    /// // make sure to re-initialise your seed!
+    /// noise.reset().with_seed(25);
+    /// let reset_10 = noise.take(10).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(first_10, reset_10);
+    /// ```
+    pub fn reset(&mut self) -> &mut Self {
+        self.init = false;
+        self.emitted = 0;
+        self.yielded = 0;
+        self.seeded = false;
+        self.max_radius = self.radius;
+        self.band_cursor = 0;
+        self.pending.clear();
+        self.checkpoints.clear();
+        self.active_points.clear();
+        for item in &mut self.grid {
+            *item = None;
+        }
+        self
+    }
+
+    /// Generates points until either the noise is exhausted or the
+    /// time budget runs out, whichever comes first. Generation can be
+    /// resumed afterwards by iterating as normal.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(10.0, 10.0, 1.0, 10);
+    /// let points = noise.generate_until(Duration::from_secs(1));
+    /// println!("generated {} points", points.len());
+    /// ```
+    pub fn generate_until(&mut self, budget: Duration) -> Vec<Vec2> {
+        self.generate_until_with_clock(budget, &SystemClock)
+    }
+
+    /// Like `generate_until`, but with time measured by the given `Clock`.
+    pub fn generate_until_with_clock<C: Clock>(
+        &mut self,
+        budget: Duration,
+        clock: &C,
+    ) -> Vec<Vec2> {
+        take_for(self, budget, clock)
+    }
+
+    /// Steps through generation one candidate at a time, rather than
+    /// one point at a time, for animating the algorithm. Every sample
+    /// tried around an active point is yielded, whether or not it was
+    /// accepted, as is every active point once it is retired.
+    ///
+    /// The accepted candidates are exactly the points the iterator would
+    /// produce. The stop condition is polled once per step, and no
+    /// checkpoints are recorded.
+    ///
+    /// ```
+    /// use bluenoise::{BlueNoise, StepDetail};
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 5.0, 10);
+    /// for step in noise.steps_detailed() {
+    ///     match step {
+    ///         StepDetail::Candidate { point, accepted, .. } => println!("{} {}", point, accepted),
+    ///         StepDetail::Retired { point } => println!("{} retired", point),
+    ///     }
+    /// }
+    /// ```
+    pub fn steps_detailed(mut self) -> impl Iterator<Item = StepDetail> {
+        let mut state = DetailState::default();
+        iter::from_fn(move || {
+            if self.should_stop() {
+                return None;
+            }
+            self.next_detail(&mut state)
+        })
+    }
+
+    /// Places a set of fixed points into the grid before generation
+    /// starts, so the noise fills in the space around them. These
+    /// points are not emitted by the iterator, and no new point will be
+    /// placed closer than the minimum radius to any of them.
+    ///
+    /// Points outside the box, or too close to an earlier point, are
+    /// rejected. With mirror symmetry, each point is placed along with
+    /// its reflection, and a point too close to its own reflection is
+    /// rejected. Returns the number of points that were accepted. If any
+    /// are accepted, generation grows outward from them instead of from a
+    /// random starting point. Resetting the generator removes them.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 10.0, 10);
+    /// let anchors = vec![Vec2::new(5.0, 5.0), Vec2::new(6.0, 5.0)];
+    /// assert_eq!(noise.insert_seed_points(anchors), 1);
+    ///
+    /// for point in noise {
+    ///     assert!(point.distance(Vec2::new(5.0, 5.0)) >= 10.0);
+    /// }
+    /// ```
+    pub fn insert_seed_points<I>(&mut self, points: I) -> usize
+    where
+        I: IntoIterator<Item = Vec2>,
+    {
+        let mut accepted = 0;
+        for point in points {
+            if self.is_valid(self.reflect_into_half(point)) {
+                self.insert_either(point);
+                accepted += 1;
+            }
+        }
+        self.pending.clear();
+        self.init |= accepted > 0;
+        self.seeded |= accepted > 0;
+        accepted
+    }
+
+    /// Feeds a set of external candidate points through the generator
+    /// before it creates any of its own. Each candidate is emitted if it
+    /// is inside the box and far enough from every point so far, and
+    /// skipped otherwise. Once the candidates run out, generation grows
+    /// outward from the accepted points to fill the gaps as normal.
+    ///
+    /// With mirror symmetry, each accepted candidate is followed by its
+    /// reflection, and a candidate too close to its own reflection is
+    /// skipped.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 10.0, 10);
+    /// let candidates = vec![Vec2::new(25.0, 25.0), Vec2::new(26.0, 25.0)];
+    /// let points = noise.with_candidates(candidates).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(points[0], Vec2::new(25.0, 25.0));
+    /// assert_ne!(points[1], Vec2::new(26.0, 25.0));
+    /// ```
+    pub fn with_candidates<I>(mut self, candidates: I) -> impl Iterator<Item = Vec2>
+    where
+        I: IntoIterator<Item = Vec2>,
+    {
+        let mut candidates = candidates.into_iter();
+        iter::from_fn(move || {
+            if self.should_stop() {
+                return None;
+            }
+            if let Some(point) = self.pending.pop() {
+                return Some(point);
+            }
+            for point in &mut candidates {
+                if self.is_valid(self.reflect_into_half(point)) {
+                    self.init = true;
+                    return Some(self.insert_either(point));
+                }
+            }
+            self.next_point()
+        })
+    }
+
+    /// A conservative estimate of the total number of points this
+    /// generator will produce, without running it. This is the same
+    /// figure used as the lower bound of `size_hint` before iterating.
+    ///
+    /// Bridson's algorithm reliably covers around 0.6 to 0.9 points per
+    /// `radius^2` depending on the sample count, so this assumes half
+    /// that, less a row of points along the edges where candidates fall
+    /// outside the box.
+    ///
+    /// Growth can die out early in a box narrower than a few radii, or
+    /// with fewer samples, fixed angles, a density function or mirror
+    /// symmetry, so then only the starting point is counted. Seed points,
+    /// a distance transform or exclusion circles can block the whole box,
+    /// so with any of those this is 0.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+    /// let estimate = noise.estimated_points();
+    /// assert!(noise.count() >= estimate);
+    /// ```
+    pub fn estimated_points(&self) -> usize {
+        if self.seeded || self.distance_transform.is_some() || self.exclusions.is_some() {
+            return 0;
+        }
+
+        // children are placed at `radius`, which `with_min_radius` can
+        // set larger than the spacing the grid checks against
+        let radius = self.radius.max(self.radius_squared.sqrt());
+        let sparse = self.max_samples < DEFAULT_SAMPLES
+            || self.fixed_angles.is_some()
+            || self.density.is_some()
+            || self.symmetry.is_some();
+        if sparse || self.width.min(self.height) < 6.0 * radius {
+            return 1;
+        }
+
+        let area = self.width * self.height / (2.0 * radius * radius);
+        let edges = (self.width + self.height) / radius;
+        (area - edges).max(1.0) as usize
+    }
+
+    /// Estimates how many values will be drawn from the rng to generate
+    /// the whole area, for pipelines that need to buffer randomness ahead
+    /// of time. Each draw is counted as a single `u32`.
+    ///
+    /// Placing the first point takes two draws, and every later step
+    /// either places a point or retires an active one. A step picks its
+    /// parent with a `usize`, which is two draws on 64-bit platforms,
+    /// and one more draw for the angle unless the angles are fixed.
+    /// This is based on the most points that could fit in the box, so it
+    /// will usually overshoot, and does not account for filling gaps when
+    /// a spacing band is set.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+    /// println!("buffer {} values", noise.estimate_rng_draws());
+    /// ```
+    pub fn estimate_rng_draws(&self) -> usize {
+        let angle = if self.fixed_angles.is_some() { 0 } else { 1 };
+        let per_step = std::mem::size_of::<usize>() / 4 + angle;
+        2 + per_step * (2 * self.max_points()).saturating_sub(1)
+    }
+
+    /// The parameters of the hexagonal grid that packs points exactly
+    /// `radius` apart, which is the ideal the noise approximates. Returns
+    /// the spacing between points along a row, and the vertical offset
+    /// between rows. Every other row is shifted along by half the spacing.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+    /// let (spacing, row_offset) = noise.reference_hex_grid();
+    ///
+    /// let hex_point = |col: usize, row: usize| {
+    ///     let shift = if row % 2 == 1 { spacing / 2.0 } else { 0.0 };
+    ///     Vec2::new(col as f32 * spacing + shift, row as f32 * row_offset)
+    /// };
+    /// assert!((hex_point(0, 0).distance(hex_point(0, 1)) - spacing).abs() < 1e-5);
+    /// ```
+    pub fn reference_hex_grid(&self) -> (f32, f32) {
+        let spacing = self.radius;
+        (spacing, spacing * 3f32.sqrt() / 2.0)
+    }
+
+    /// The number of bytes the generator holds on the heap. This is
+    /// mostly the grid, which has a slot for every cell, so it is known
+    /// up front and grows with the area over `radius^2`. On top of that
+    /// are the active and pending points, and the distance transform and
+    /// exclusion circles if set.
+    ///
+    /// Each checkpoint holds a full copy of the grid, so with checkpoints
+    /// enabled this grows as they are recorded. Anything captured by the
+    /// density function or stop condition isn't counted.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(1000.0, 1000.0, 1.0, 10);
+    /// assert!(noise.memory_usage() < 32 * 1024 * 1024);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        let grid = self.grid_width * self.grid_height * std::mem::size_of::<Option<Vec2>>();
+        let points =
+            (self.active_points.capacity() + self.pending.capacity()) * std::mem::size_of::<Vec2>();
+        let transform = self.distance_transform.as_ref().map_or(0, |transform| {
+            transform.grid.capacity() * std::mem::size_of::<f32>()
+        });
+        let exclusions = self
+            .exclusions
+            .as_ref()
+            .map_or(0, |exclusions| exclusions.memory_usage());
+        let checkpoints = self.checkpoints.capacity() * std::mem::size_of::<Snapshot<R>>()
+            + self
+                .checkpoints
+                .iter()
+                .map(|snapshot| snapshot.memory_usage())
+                .sum::<usize>();
+        grid + points + transform + exclusions + checkpoints
+    }
+
+    /// Finds the largest circle, centred inside the box `bounds`, that
+    /// contains none of the given points. Its radius is the dispersion
+    /// of the set, and the smaller it is the better the box is covered.
+    /// Returns the centre and radius of the circle.
+    ///
+    /// The centre of the largest empty circle is either a vertex of the
+    /// Voronoi diagram of the points, where a Voronoi edge meets the
+    /// boundary, or a corner of the box. Candidates are only built from
+    /// points within a few multiples of this generator's radius of each
+    /// other, so the points are assumed to be about as dense as those it
+    /// produces.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use glam::Vec2;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+    /// let points = noise.clone().collect::<Vec<_>>();
+    /// let (center, radius) = noise.largest_empty_circle(&points, Vec2::new(50.0, 50.0));
+    ///
+    /// println!("largest gap of {} at {}", radius, center);
+    /// ```
+    pub fn largest_empty_circle(&self, points: &[Vec2], bounds: Vec2) -> (Vec2, f32) {
+        if points.is_empty() {
+            return (bounds / 2.0, bounds.length() / 2.0);
+        }
+
+        // bucket the points so we only look at nearby ones
+        let cell_size = self.radius;
+        let grid_width = ((bounds.x / cell_size).ceil() as usize).max(1);
+        let grid_height = ((bounds.y / cell_size).ceil() as usize).max(1);
+        let cell = |point: Vec2| {
+            let x = (point.x / cell_size).max(0.0) as usize;
+            let y = (point.y / cell_size).max(0.0) as usize;
+            (x.min(grid_width - 1), y.min(grid_height - 1))
+        };
+        let mut buckets = vec![Vec::new(); grid_width * grid_height];
+        for (index, point) in points.iter().enumerate() {
+            let (x, y) = cell(*point);
+            buckets[y * grid_width + x].push(index);
+        }
+        let nearby = |x: usize, y: usize, reach: usize| {
+            (x.saturating_sub(reach)..(x + reach + 1).min(grid_width))
+                .cartesian_product(y.saturating_sub(reach)..(y + reach + 1).min(grid_height))
+                .flat_map(|(x, y)| buckets[y * grid_width + x].iter().copied())
+        };
+
+        // search outward ring by ring for the closest point
+        let nearest = |center: Vec2| {
+            let (x, y) = cell(center);
+            let mut best = f32::INFINITY;
+            for ring in 0..=grid_width.max(grid_height) {
+                if (ring as f32 - 1.0) * cell_size > best {
+                    break;
+                }
+                for index in nearby(x, y, ring) {
+                    let (px, py) = cell(points[index]);
+                    let on_ring = px.max(x) - px.min(x) == ring || py.max(y) - py.min(y) == ring;
+                    if on_ring {
+                        best = best.min(center.distance(points[index]));
+                    }
+                }
+            }
+            best
+        };
+
+        let inside = |point: Vec2| {
+            point.x >= 0.0 && point.x <= bounds.x && point.y >= 0.0 && point.y <= bounds.y
+        };
+        let mut candidates = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(bounds.x, 0.0),
+            Vec2::new(0.0, bounds.y),
+            bounds,
+        ];
+        for (i, a) in points.iter().enumerate() {
+            let (x, y) = cell(*a);
+            let neighbours = nearby(x, y, 3).filter(|j| *j > i).collect::<Vec<_>>();
+            for (n, j) in neighbours.iter().enumerate() {
+                let b = points[*j];
+
+                // where the bisector of the two points meets the boundary
+                let mid = (*a + b) / 2.0;
+                let dir = b - *a;
+                if dir.y != 0.0 {
+                    for edge in &[0.0, bounds.x] {
+                        candidates.push(Vec2::new(*edge, mid.y - (edge - mid.x) * dir.x / dir.y));
+                    }
+                }
+                if dir.x != 0.0 {
+                    for edge in &[0.0, bounds.y] {
+                        candidates.push(Vec2::new(mid.x - (edge - mid.y) * dir.y / dir.x, *edge));
+                    }
+                }
+
+                // and the circumcentres, which include the voronoi vertices
+                for k in &neighbours[n + 1..] {
+                    candidates.extend(circumcenter(*a, b, points[*k]));
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter(|center| inside(*center))
+            .map(|center| (center, nearest(center)))
+            .fold((bounds / 2.0, 0.0), |best, next| {
+                if next.1 > best.1 {
+                    next
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// An upper bound on the number of points that can fit in the box,
+    /// using Groemer's bound for points a minimum distance apart.
+    fn max_points(&self) -> usize {
+        let area = self.width * self.height;
+        let perimeter = 2.0 * (self.width + self.height);
+        let radius = self.radius_squared.sqrt();
+        (2.0 * area / (3f32.sqrt() * self.radius_squared) + perimeter / (2.0 * radius) + 1.0)
+            as usize
+    }
+
+    /// Compute the distance between two points
+    fn distance(&self, point: Vec2, target: Vec2) -> f32 {
+        point.distance_squared(target)
+    }
+
+    /// Check if the minimum radius varies by position.
+    fn is_variable(&self) -> bool {
+        self.density.is_some()
+            || self
+                .distance_transform
+                .as_ref()
+                .is_some_and(|transform| transform.radius_scale.is_some())
+    }
+
+    /// Get the minimum radius around a position.
+    fn radius_at(&self, position: Vec2) -> f32 {
+        let mut radius = self.radius;
+        if let Some(density) = &self.density {
+            radius = radius.max(density.0(position));
+        }
+        if let Some(transform) = &self.distance_transform {
+            if let Some(scale) = transform.radius_scale {
+                radius = radius.max(scale * self.clearance(transform, position));
+            }
+        }
+        radius
+    }
+
+    /// Get the squared distance a point with the given local
+    /// radius must keep from an existing point.
+    fn min_distance_squared(&self, radius: f32, target: Vec2) -> f32 {
+        if self.is_variable() {
+            radius.max(self.radius_at(target)).powi(2)
+        } else {
+            self.radius_squared
+        }
+    }
+
+    /// Get the number of cells to search in each direction for
+    /// points that may conflict with one of the given radius.
+    fn reach(&self, radius: f32) -> usize {
+        if self.is_variable() {
+            (radius.max(self.max_radius) / self.cell_size).ceil() as usize
+        } else {
+            2
+        }
+    }
+
+    /// Check if a position is inside the box.
+    fn contains(&self, point: Vec2) -> bool {
+        point.x >= 0.0 && point.x < self.width && point.y >= 0.0 && point.y < self.height
+    }
+
+    /// Check if a position is far enough away from
+    /// nearby previously created points.
+    fn is_valid(&self, point: Vec2) -> bool {
+        // remove anything outside our box
+        if !self.contains(point) {
+            return false;
+        };
+
+        // and make sure the reflection has room too
+        if let Some(mirror) = self.mirror(point) {
+            let radius = self.radius_at(point);
+            if !self.in_half(point)
+                || (mirror != point
+                    && (self.distance(point, mirror) < self.min_distance_squared(radius, mirror)
+                        || !self.is_clear(mirror)))
+            {
+                return false;
+            }
+        }
+
+        self.is_clear(point)
+    }
+
+    /// Check that a position is clear of obstacles, and that every
+    /// nearby point is further than our min radius.
+    fn is_clear(&self, point: Vec2) -> bool {
+        if !self.is_allowed(point) {
+            return false;
+        }
+
+        let radius = self.radius_at(point);
+        self.neighbours(point, self.reach(radius))
+            .all(|target| self.distance(point, target) >= self.min_distance_squared(radius, target))
+    }
+
+    /// Check that a position isn't ruled out by any obstacles.
+    fn is_allowed(&self, point: Vec2) -> bool {
+        if let Some(exclusions) = &self.exclusions {
+            if exclusions.contains(point) {
+                return false;
+            }
+        }
+        match &self.distance_transform {
+            Some(transform) => self.clearance(transform, point) >= transform.min_clearance,
+            None => true,
+        }
+    }
+
+    /// Look up the clearance of the cell a position falls in.
+    fn clearance(&self, transform: &DistanceTransform, point: Vec2) -> f32 {
+        let col = ((point.x / self.width * transform.cols as f32).max(0.0) as usize)
+            .min(transform.cols - 1);
+        let row = ((point.y / self.height * transform.rows as f32).max(0.0) as usize)
+            .min(transform.rows - 1);
+        transform.grid[row * transform.cols + col]
+    }
+
+    /// Reflect a point across the axis of symmetry, if there is one.
+    fn mirror(&self, point: Vec2) -> Option<Vec2> {
+        self.symmetry.map(|axis| match axis {
+            Axis::Vertical => Vec2::new(self.width - point.x, point.y),
+            Axis::Horizontal => Vec2::new(point.x, self.height - point.y),
+        })
+    }
+
+    /// Check if a point is in the half of the box that we generate in.
+    fn in_half(&self, point: Vec2) -> bool {
+        match self.symmetry {
+            Some(Axis::Vertical) => point.x > 0.0 && point.x <= self.width / 2.0,
+            Some(Axis::Horizontal) => point.y > 0.0 && point.y <= self.height / 2.0,
+            None => true,
+        }
+    }
+
+    /// Move a point in the mirrored half to its reflection, so that
+    /// it is in the half of the box that we generate in.
+    fn reflect_into_half(&self, point: Vec2) -> Vec2 {
+        match self.mirror(point) {
+            Some(mirror) if !self.in_half(point) => mirror,
+            _ => point,
+        }
+    }
+
+    /// Pick a random starting point, in the generated half if the
+    /// noise is symmetric.
+    fn initial_point(&mut self) -> Vec2 {
+        let (width, height) = match self.symmetry {
+            Some(Axis::Vertical) => (self.width / 2.0, self.height),
+            Some(Axis::Horizontal) => (self.width, self.height / 2.0),
+            None => (self.width, self.height),
+        };
+        let x = self.rng.gen_range(0.0..width);
+        let y = self.rng.gen_range(0.0..height);
+        Vec2::new(x, y)
+    }
+
+    /// Move a point onto the axis of symmetry, so that it is its own
+    /// reflection. Used when the starting point is too close to it.
+    fn snap_to_axis(&self, point: Vec2) -> Vec2 {
+        match self.symmetry {
+            Some(Axis::Vertical) => Vec2::new(self.width / 2.0, point.y),
+            Some(Axis::Horizontal) => Vec2::new(point.x, self.height / 2.0),
+            None => point,
+        }
+    }
+
+    /// Get the centres of the cells that might hold a starting point,
+    /// first those of the distance transform with enough clearance,
+    /// then those of the grid.
+    fn start_candidates(&self) -> impl Iterator<Item = Vec2> + '_ {
+        let clear = self.distance_transform.iter().flat_map(move |transform| {
+            let size = Vec2::new(
+                self.width / transform.cols as f32,
+                self.height / transform.rows as f32,
+            );
+            (0..transform.rows)
+                .cartesian_product(0..transform.cols)
+                .filter(move |(y, x)| {
+                    transform.grid[y * transform.cols + x] >= transform.min_clearance
+                })
+                .map(move |(y, x)| (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) * size)
+        });
+        let cells = (0..self.grid_height)
+            .cartesian_product(0..self.grid_width)
+            .map(move |(y, x)| (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) * self.cell_size);
+        clear.chain(cells)
+    }
+
+    /// Get the points in the grid within `reach` cells of a position.
+    fn neighbours(&self, point: Vec2, reach: usize) -> impl Iterator<Item = Vec2> + '_ {
+        let x_range = {
+            let x = (point.x / self.cell_size) as usize;
+            x.saturating_sub(reach)..(x + reach + 1).min(self.grid_width)
+        };
+        let y_range = {
+            let y = (point.y / self.cell_size) as usize;
+            y.saturating_sub(reach)..(y + reach + 1).min(self.grid_height)
+        };
+
+        x_range
+            .cartesian_product(y_range)
+            .filter_map(move |(x, y)| {
+                *self
+                    .grid
+                    .get(y * self.grid_width + x)
+                    .expect("Ended up out of bounds when fetching point.")
+            })
+    }
+
+    /// Check if a point has no neighbours within some distance.
+    fn is_isolated(&self, point: Vec2, max_spacing: f32) -> bool {
+        let reach = (max_spacing / self.cell_size).ceil() as usize;
+        self.neighbours(point, reach)
+            .all(|target| target == point || self.distance(point, target) > max_spacing.powi(2))
+    }
+
+    /// Get some point in the spacing band around a position
+    fn get_banded(&mut self, position: Vec2, max_spacing: f32) -> Vec2 {
+        let theta = 2.0 * PI * self.rng.gen::<f32>();
+        let min = self.radius_at(position) + 0.001;
+        let radius = min + (max_spacing - min).max(0.0) * self.rng.gen::<f32>();
+        Vec2::new(
+            position.x + radius * theta.cos(),
+            position.y + radius * theta.sin(),
+        )
+    }
+
+    /// Poll the stop condition, if there is one.
+    fn should_stop(&self) -> bool {
+        match &self.stop_condition {
+            Some(stop_condition) => {
+                let mut stop_condition = stop_condition
+                    .0
+                    .lock()
+                    .expect("The stop condition panicked.");
+                (*stop_condition)()
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether there is nothing left to generate.
+    fn is_exhausted(&self) -> bool {
+        self.init
+            && self.active_points.is_empty()
+            && self.pending.is_empty()
+            && (self.max_spacing.is_none() || self.band_cursor >= self.grid.len())
+    }
+
+    /// Get the number of samples to try around each active point.
+    fn samples(&self) -> u32 {
+        self.fixed_angles.unwrap_or(self.max_samples)
+    }
+
+    /// Get the rotation of the samples around a parent, as a fraction
+    /// of a turn. With fixed angles this is a hash of the parent's
+    /// position, so that no randomness is used.
+    fn parent_offset(&mut self, parent: Vec2) -> f32 {
+        if self.fixed_angles.is_none() {
+            return self.rng.gen::<f32>();
+        }
+
+        let z = splitmix(u64::from(parent.x.to_bits()) << 32 | u64::from(parent.y.to_bits()));
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Get some nearby point
+    fn get_nearby(&mut self, position: Vec2, seed: f32, sample: u32) -> Vec2 {
+        let offset = seed + sample as f32 / self.samples() as f32;
+        let theta = 2.0 * PI * offset;
+        let radius = self.radius_at(position) + 0.001;
+        Vec2::new(
+            position.x + radius * theta.cos(),
+            position.y + radius * theta.sin(),
+        )
+    }
+
+    /// Get the index for a given position
+    fn grid_index(&self, position: Vec2) -> usize {
         let y = self.grid_width * (position.y / self.cell_size) as usize;
         let x = (position.x / self.cell_size) as usize;
         let out = y + x;
 
-        assert_ne!(self.grid_width * self.grid_height, x);
+        assert_ne!(self.grid_width * self.grid_height, x);
+
+        out
+    }
+
+    /// Insert a point into the grid and mark it active. If the noise
+    /// is symmetric, the reflection is added to the grid and queued.
+    fn insert_point(&mut self, position: Vec2) -> Vec2 {
+        self.place_point(position);
+        self.active_points.push(position);
+        if let Some(mirror) = self.mirror(position).filter(|m| *m != position) {
+            self.place_point(mirror);
+            self.pending.push(mirror);
+        }
+        position
+    }
+
+    /// Insert a point from either half, along with its reflection.
+    /// The point is returned and its reflection queued, the same as
+    /// for one in the generated half.
+    fn insert_either(&mut self, position: Vec2) -> Vec2 {
+        let half = self.reflect_into_half(position);
+        self.insert_point(half);
+        if half != position {
+            if let Some(pending) = self.pending.last_mut() {
+                *pending = half;
+            }
+        }
+        position
+    }
+
+    /// Insert a point into the grid
+    fn place_point(&mut self, position: Vec2) {
+        let index = self.grid_index(position);
+        self.grid[index] = Some(position);
+        self.emitted += 1;
+        if self.is_variable() {
+            self.max_radius = self.max_radius.max(self.radius_at(position));
+        }
+    }
+
+    /// Fill the rectangle from `min` to `max` with points, growing out
+    /// from any points already in the grid just outside it, or from a
+    /// random point if there are none, and then from any gaps left.
+    fn fill_patch(&mut self, min: Vec2, max: Vec2) -> Vec<Vec2> {
+        let inside = |p: Vec2| p.x >= min.x && p.x < max.x && p.y >= min.y && p.y < max.y;
+
+        // find the points close enough to have children in the patch
+        let margin = 2.0 * self.radius;
+        let cell = |value: f32| (value / self.cell_size).max(0.0) as usize;
+        let columns = cell(min.x - margin)..(cell(max.x + margin) + 1).min(self.grid_width);
+        let rows = cell(min.y - margin)..(cell(max.y + margin) + 1).min(self.grid_height);
+        self.active_points = rows
+            .cartesian_product(columns)
+            .filter_map(|(y, x)| self.grid[y * self.grid_width + x])
+            .filter(|point| !inside(*point))
+            .collect();
+
+        let mut points = Vec::new();
+        if self.active_points.is_empty() {
+            for _ in 0..INITIAL_ATTEMPTS {
+                let point = min + (max - min) * Vec2::new(self.rng.gen(), self.rng.gen());
+                if self.is_valid(point) {
+                    points.push(self.insert_point(point));
+                    break;
+                }
+            }
+        }
+
+        // a fine lattice over the patch, to find any room growth missed
+        let step = self.cell_size / 2.0;
+        let lattice_width = ((max.x - min.x) / step).ceil() as usize;
+        let lattice_height = ((max.y - min.y) / step).ceil() as usize;
+        let mut lattice = (0..lattice_height)
+            .cartesian_product(0..lattice_width)
+            .map(move |(y, x)| min + (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) * step);
+
+        loop {
+            while !self.active_points.is_empty() {
+                let index = self.rng.gen_range(0..self.active_points.len());
+                let parent = self.active_points[index];
+
+                let seed = self.parent_offset(parent);
+                let mut child = None;
+                for sample in 0..self.samples() {
+                    let point = self.get_nearby(parent, seed, sample);
+                    if inside(point) && self.is_valid(point) {
+                        child = Some(point);
+                        break;
+                    }
+                }
+
+                match child {
+                    Some(point) => points.push(self.insert_point(point)),
+                    None => {
+                        self.active_points.swap_remove(index);
+                    }
+                }
+            }
+
+            // growth from the edges can't reach every corner of a small
+            // patch, so start again from anywhere there is still room
+            match lattice.find(|point| inside(*point) && self.is_valid(*point)) {
+                Some(point) => points.push(self.insert_point(point)),
+                None => break,
+            }
+        }
+        points
+    }
+}
+
+impl<R: Rng + Clone> BlueNoise<R> {
+    /// A builder function to record a checkpoint every `interval`
+    /// points, so that generation can be rewound and replayed. The
+    /// first checkpoint is the state before any points are generated,
+    /// and checkpoint `n` is the state after the iterator has returned
+    /// `n * interval` points, counting reflections but not seed points.
+    ///
+    /// Each checkpoint holds a copy of the grid, so pick an interval
+    /// that keeps the number of them reasonable.
+    ///
+    /// Checkpoints are only recorded as each one is reached, so if they
+    /// are enabled partway through, the ones already passed are skipped.
+    /// Changing the interval throws away any checkpoints recorded so far
+    /// and starts again from the current point, but calling this again
+    /// with the same interval keeps them. That is how to enable
+    /// checkpoints again after deserializing.
+    ///
+    /// ```
+    /// use bluenoise::BlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+    /// let noise = noise.with_checkpoints(100);
+    /// let first = noise.take(300).collect::<Vec<_>>();
+    ///
+    /// noise.rewind_to(1);
+    /// let replay = noise.take(200).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(first[100..], replay[..]);
+    /// ```
+    pub fn with_checkpoints(&mut self, interval: usize) -> &mut Self {
+        let interval = interval.max(1);
+        if self.checkpoint_interval != Some(interval) {
+            self.checkpoint_interval = Some(interval);
+            self.checkpoints.clear();
+        }
+        self.snapshot = Some(Self::snapshot);
+        self
+    }
+
+    /// Restores the generator to a previously recorded checkpoint.
+    /// Later checkpoints are kept, so it is possible to scrub both
+    /// backward and forward.
+    ///
+    /// For an example, see `BlueNoise::with_checkpoints`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint hasn't been recorded, either because it
+    /// hasn't been reached yet or because it was passed before checkpoints
+    /// were enabled.
+    pub fn rewind_to(&mut self, checkpoint: usize) -> &mut Self {
+        let index = self
+            .checkpoints
+            .binary_search_by_key(&checkpoint, |snapshot| snapshot.milestone)
+            .expect("The checkpoint hasn't been recorded.");
+        let snapshot = self.checkpoints[index].clone();
+
+        self.grid = snapshot.grid;
+        self.active_points = snapshot.active_points;
+        self.rng = snapshot.rng;
+        self.init = snapshot.init;
+        self.emitted = snapshot.emitted;
+        self.yielded = snapshot.yielded;
+        self.max_radius = snapshot.max_radius;
+        self.band_cursor = snapshot.band_cursor;
+        self.pending = snapshot.pending;
+        self
+    }
+
+    /// Copy the current state of the generator
+    fn snapshot(&self) -> Snapshot<R> {
+        Snapshot {
+            milestone: self.yielded / self.checkpoint_interval.unwrap_or(1),
+            grid: self.grid.clone(),
+            active_points: self.active_points.clone(),
+            rng: self.rng.clone(),
+            init: self.init,
+            emitted: self.emitted,
+            yielded: self.yielded,
+            max_radius: self.max_radius,
+            band_cursor: self.band_cursor,
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<R: Rng> BlueNoise<R> {
+    /// The number of checkpoints recorded so far.
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Record any checkpoints that have been reached
+    fn record_checkpoint(&mut self) {
+        if let (Some(interval), Some(snapshot)) = (self.checkpoint_interval, self.snapshot) {
+            if !self.yielded.is_multiple_of(interval) {
+                return;
+            }
+            let milestone = self.yielded / interval;
+            let position = self
+                .checkpoints
+                .binary_search_by_key(&milestone, |snapshot| snapshot.milestone);
+            if let Err(index) = position {
+                let snapshot = snapshot(self);
+                self.checkpoints.insert(index, snapshot);
+            }
+        }
+    }
+
+    /// Generate the next point, without polling the stop condition.
+    fn next_point(&mut self) -> Option<Vec2> {
+        if let Some(point) = self.pending.pop() {
+            return Some(point);
+        }
+
+        if !self.init {
+            self.init = true;
+            for _ in 0..INITIAL_ATTEMPTS {
+                // a point too close to the axis is moved onto it
+                let mut point = self.initial_point();
+                if !self.is_valid(point) {
+                    point = self.snap_to_axis(point);
+                }
+                if self.is_valid(point) {
+                    return Some(self.insert_point(point));
+                }
+            }
+
+            // most of the box is blocked, so search the cells in order
+            let start = self
+                .start_candidates()
+                .map(|point| {
+                    if self.is_valid(point) {
+                        point
+                    } else {
+                        self.snap_to_axis(point)
+                    }
+                })
+                .find(|point| self.is_valid(*point));
+            return start.map(|point| self.insert_point(point));
+        }
+
+        while !self.active_points.is_empty() {
+            let index = self.rng.gen_range(0..self.active_points.len());
+            let parent = self.active_points[index];
+
+            let seed = self.parent_offset(parent);
+            for sample in 0..self.samples() {
+                let point = self.get_nearby(parent, seed, sample);
+                if self.is_valid(point) {
+                    return Some(self.insert_point(point));
+                }
+            }
+
+            self.active_points.swap_remove(index);
+        }
+
+        // once the area is full, fill in any gaps that are too wide
+        if let Some(max_spacing) = self.max_spacing {
+            while let Some(cell) = self.grid.get(self.band_cursor) {
+                if let Some(parent) = *cell {
+                    if self.is_isolated(parent, max_spacing) {
+                        for _ in 0..self.max_samples {
+                            let point = self.get_banded(parent, max_spacing);
+                            if self.is_valid(point) {
+                                return Some(self.insert_point(point));
+                            }
+                        }
+                    }
+                }
+                self.band_cursor += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Evaluate the next candidate. This mirrors `next_point`, but
+    /// keeps track of where it is in the step between calls.
+    fn next_detail(&mut self, state: &mut DetailState) -> Option<StepDetail> {
+        if let Some(point) = self.pending.pop() {
+            return Some(StepDetail::Candidate {
+                point,
+                accepted: true,
+                parent: None,
+            });
+        }
+
+        if !self.init {
+            return self.next_point().map(|point| StepDetail::Candidate {
+                point,
+                accepted: true,
+                parent: None,
+            });
+        }
+
+        if state.attempt.is_none() && !self.active_points.is_empty() {
+            let index = self.rng.gen_range(0..self.active_points.len());
+            let parent = self.active_points[index];
+            let seed = self.parent_offset(parent);
+            state.attempt = Some((index, parent, seed, 0));
+        }
+
+        if let Some((index, parent, seed, sample)) = state.attempt {
+            if sample >= self.samples() {
+                state.attempt = None;
+                let point = self.active_points.swap_remove(index);
+                return Some(StepDetail::Retired { point });
+            }
+
+            let point = self.get_nearby(parent, seed, sample);
+            let accepted = self.is_valid(point);
+            state.attempt = if accepted {
+                self.insert_point(point);
+                None
+            } else {
+                Some((index, parent, seed, sample + 1))
+            };
+            return Some(StepDetail::Candidate {
+                point,
+                accepted,
+                parent: Some(parent),
+            });
+        }
+
+        if let Some(max_spacing) = self.max_spacing {
+            while let Some(cell) = self.grid.get(self.band_cursor) {
+                if let Some(parent) = *cell {
+                    if state.band_sample < self.max_samples && self.is_isolated(parent, max_spacing)
+                    {
+                        let point = self.get_banded(parent, max_spacing);
+                        let accepted = self.is_valid(point);
+                        if accepted {
+                            self.insert_point(point);
+                            state.band_sample = 0;
+                        } else {
+                            state.band_sample += 1;
+                        }
+                        return Some(StepDetail::Candidate {
+                            point,
+                            accepted,
+                            parent: Some(parent),
+                        });
+                    }
+                }
+                state.band_sample = 0;
+                self.band_cursor += 1;
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: Rng> Iterator for BlueNoise<R> {
+    type Item = Vec2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.should_stop() {
+            return None;
+        }
+
+        self.record_checkpoint();
+        let point = self.next_point();
+        self.yielded += usize::from(point.is_some());
+        point
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.is_exhausted() {
+            return (0, Some(0));
+        }
+
+        // we could be stopped at any point
+        if self.stop_condition.is_some() {
+            return (0, Some(self.max_points().saturating_sub(self.emitted)));
+        }
+
+        (
+            self.estimated_points().saturating_sub(self.emitted),
+            Some(self.max_points().saturating_sub(self.emitted)),
+        )
+    }
+}
+
+/// Provides a source of `WrappingBlueNoise` in a given area at some
+/// density, where the distance between two points wraps around the
+/// edges of the box. This can be used to generate tiling blue noise.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WrappingBlueNoise<R: Rng>(BlueNoise<R>);
+
+impl<R: Rng + SeedableRng> WrappingBlueNoise<R> {
+    /// Creates a new instance of `WrappingBlueNoise`.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `min_radius`: The minimum distance between points.
+    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
+    pub fn new(width: f32, height: f32, min_radius: f32) -> Self {
+        Self(BlueNoise::new(width, height, min_radius))
+    }
+
+    /// Creates a new instance of `WrappingBlueNoise`.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `min_radius`: The minimum distance between points.
+    /// * `seed`: Value to seed the rng with
+    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
+    pub fn from_seed(width: f32, height: f32, min_radius: f32, seed: u64) -> Self {
+        Self(BlueNoise::from_seed(width, height, min_radius, seed))
+    }
+
+    /// A builder function to seed the rng with a specific
+    /// value.
+    ///
+    /// For an example, see the `WrappingBlueNoise` examples.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.0.with_seed(seed);
+        self
+    }
+}
+
+impl<R: Rng + Clone> WrappingBlueNoise<R> {
+    /// A builder function to record a checkpoint every `interval`
+    /// points, so that generation can be rewound and replayed.
+    ///
+    /// For more details, see `BlueNoise::with_checkpoints`.
+    pub fn with_checkpoints(&mut self, interval: usize) -> &mut Self {
+        self.0.with_checkpoints(interval);
+        self
+    }
+
+    /// Restores the generator to a previously recorded checkpoint.
+    ///
+    /// For more details, see `BlueNoise::rewind_to`.
+    pub fn rewind_to(&mut self, checkpoint: usize) -> &mut Self {
+        self.0.rewind_to(checkpoint);
+        self
+    }
+}
+
+impl<R: Rng> WrappingBlueNoise<R> {
+    /// Creates a new instance of `WrappingBlueNoise`.
+    ///
+    /// * `width`: The width of the box to generate inside.
+    /// * `height`: The height of the box to generate inside.
+    /// * `min_radius`: The minimum distance between points.
+    /// * `rng`: Rng to use
+    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
+    pub fn from_rng(width: f32, height: f32, min_radius: f32, rng: R) -> Self {
+        Self(BlueNoise::from_rng(width, height, min_radius, rng))
+    }
+
+    /// A builder function to set the maximum number of
+    /// samples to be when attempting to find new points.
+    ///
+    /// For an example, see the `WrappingBlueNoise` examples.
+    pub fn with_samples(&mut self, max_samples: u32) -> &mut Self {
+        self.0.with_samples(max_samples);
+        self
+    }
+
+    /// A builder function to only place points that are far enough
+    /// from obstacles.
+    ///
+    /// For more details, see `BlueNoise::with_distance_transform`.
+    pub fn with_distance_transform(
+        &mut self,
+        grid: Vec<f32>,
+        cols: usize,
+        rows: usize,
+        min_clearance: f32,
+    ) -> &mut Self {
+        self.0
+            .with_distance_transform(grid, cols, rows, min_clearance);
+        self
+    }
+
+    /// A builder function to grow the minimum radius with the clearance
+    /// in the distance transform.
+    ///
+    /// For more details, see `BlueNoise::with_clearance_scaling`.
+    pub fn with_clearance_scaling(&mut self, scale: f32) -> &mut Self {
+        self.0.with_clearance_scaling(scale);
+        self
+    }
+
+    /// A builder function to keep points out of a set of circles.
+    ///
+    /// For more details, see `BlueNoise::with_exclusion_circles`.
+    pub fn with_exclusion_circles(&mut self, circles: Vec<(Vec2, f32)>) -> &mut Self {
+        self.0.with_exclusion_circles(circles);
+        self
+    }
+
+    /// A builder function to try exactly `k` evenly spaced angles
+    /// around each point.
+    ///
+    /// For more details, see `BlueNoise::with_fixed_angles`.
+    pub fn with_fixed_angles(&mut self, k: u32) -> &mut Self {
+        self.0.with_fixed_angles(k);
+        self
+    }
+
+    /// A builder function to set the minimum radius between
+    /// points.
+    ///
+    /// For an example, see the `WrappingBlueNoise` examples.
+    pub fn with_min_radius(&mut self, min_radius: f32) -> &mut Self {
+        self.0.with_min_radius(min_radius);
+        self
+    }
+
+    /// A builder function to vary the minimum radius between points
+    /// by position.
+    ///
+    /// For more details, see `BlueNoise::with_density`.
+    pub fn with_density<F>(&mut self, min_radius: f32, density: F) -> &mut Self
+    where
+        F: Fn(Vec2) -> f32 + Send + Sync + 'static,
+    {
+        self.0.with_density(min_radius, density);
+        self
+    }
+
+    /// Replaces the function that varies the minimum radius, without
+    /// touching the grid.
+    ///
+    /// For more details, see `BlueNoise::set_density`.
+    pub fn set_density<F>(&mut self, density: F) -> &mut Self
+    where
+        F: Fn(Vec2) -> f32 + Send + Sync + 'static,
+    {
+        self.0.set_density(density);
+        self
+    }
+
+    /// A builder function to limit the spacing between points to a band.
+    ///
+    /// For more details, see `BlueNoise::with_spacing_band`.
+    pub fn with_spacing_band(&mut self, min_radius: f32, max_radius: f32) -> &mut Self {
+        self.0.with_spacing_band(min_radius, max_radius);
+        self
+    }
+
+    /// A builder function to make the noise symmetric about an axis
+    /// through the centre of the box.
+    ///
+    /// For more details, see `BlueNoise::with_mirror_symmetry`.
+    pub fn with_mirror_symmetry(&mut self, axis: Axis) -> &mut Self {
+        self.0.with_mirror_symmetry(axis);
+        self
+    }
+
+    /// A builder function to stop generation cooperatively.
+    ///
+    /// For more details, see `BlueNoise::with_stop_condition`.
+    pub fn with_stop_condition<F>(&mut self, stop_condition: F) -> &mut Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        self.0.with_stop_condition(stop_condition);
+        self
+    }
+
+    /// Resets the generator to begin creating noise from the beginning.
+    /// This will not reset the prng so if you want deterministic ordering,
+    /// make sure to set it explicitly.
+    ///
+    /// ```
+    /// use bluenoise::WrappingBlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = WrappingBlueNoise::<Pcg64Mcg>::new(10.0, 10.0, 1.0);
+    /// let first_10 = noise.with_seed(25).take(10).collect::<Vec<_>>();
+    ///
+    /// // make sure to re-initialise your seed!
+    /// noise.reset().with_seed(25);
+    /// let reset_10 = noise.take(10).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(first_10, reset_10);
+    /// ```
+    pub fn reset(&mut self) -> &mut Self {
+        self.0.reset();
+        self
+    }
+
+    /// Generates points until either the noise is exhausted or the
+    /// time budget runs out, whichever comes first. Generation can be
+    /// resumed afterwards by iterating as normal.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bluenoise::WrappingBlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(10.0, 10.0, 1.0, 10);
+    /// let points = noise.generate_until(Duration::from_secs(1));
+    /// println!("generated {} points", points.len());
+    /// ```
+    pub fn generate_until(&mut self, budget: Duration) -> Vec<Vec2> {
+        self.generate_until_with_clock(budget, &SystemClock)
+    }
+
+    /// Like `generate_until`, but with time measured by the given `Clock`.
+    pub fn generate_until_with_clock<C: Clock>(
+        &mut self,
+        budget: Duration,
+        clock: &C,
+    ) -> Vec<Vec2> {
+        take_for(self, budget, clock)
+    }
+
+    /// Steps through generation one candidate at a time, rather than
+    /// one point at a time, for animating the algorithm.
+    ///
+    /// For more details, see `BlueNoise::steps_detailed`.
+    pub fn steps_detailed(mut self) -> impl Iterator<Item = StepDetail> {
+        let mut state = DetailState::default();
+        iter::from_fn(move || {
+            if self.0.should_stop() {
+                return None;
+            }
+            self.next_detail(&mut state)
+        })
+    }
+
+    /// Places a set of fixed points into the grid before generation
+    /// starts, so the noise fills in the space around them.
+    ///
+    /// For more details, see `BlueNoise::insert_seed_points`.
+    pub fn insert_seed_points<I>(&mut self, points: I) -> usize
+    where
+        I: IntoIterator<Item = Vec2>,
+    {
+        let mut accepted = 0;
+        for point in points {
+            if self.0.contains(point) && self.is_valid(self.0.reflect_into_half(point)) {
+                self.0.insert_either(point);
+                accepted += 1;
+            }
+        }
+        self.0.pending.clear();
+        self.0.init |= accepted > 0;
+        self.0.seeded |= accepted > 0;
+        accepted
+    }
+
+    /// Feeds a set of external candidate points through the generator
+    /// before it creates any of its own.
+    ///
+    /// For more details, see `BlueNoise::with_candidates`.
+    pub fn with_candidates<I>(mut self, candidates: I) -> impl Iterator<Item = Vec2>
+    where
+        I: IntoIterator<Item = Vec2>,
+    {
+        let mut candidates = candidates.into_iter();
+        iter::from_fn(move || {
+            if self.0.should_stop() {
+                return None;
+            }
+            if let Some(point) = self.0.pending.pop() {
+                return Some(point);
+            }
+            for point in &mut candidates {
+                if self.0.contains(point) && self.is_valid(self.0.reflect_into_half(point)) {
+                    self.0.init = true;
+                    return Some(self.0.insert_either(point));
+                }
+            }
+            self.next_point()
+        })
+    }
+
+    /// A conservative estimate of the total number of points this
+    /// generator will produce, without running it.
+    ///
+    /// For more details, see `BlueNoise::estimated_points`.
+    ///
+    /// ```
+    /// use bluenoise::WrappingBlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+    /// let estimate = noise.estimated_points();
+    /// assert!(noise.count() >= estimate);
+    /// ```
+    pub fn estimated_points(&self) -> usize {
+        self.0.estimated_points()
+    }
+
+    /// Estimates how many values will be drawn from the rng to
+    /// generate the whole area.
+    ///
+    /// For more details, see `BlueNoise::estimate_rng_draws`.
+    pub fn estimate_rng_draws(&self) -> usize {
+        self.0.estimate_rng_draws()
+    }
+
+    /// The parameters of the hexagonal grid that packs points
+    /// exactly `radius` apart.
+    ///
+    /// For more details, see `BlueNoise::reference_hex_grid`.
+    pub fn reference_hex_grid(&self) -> (f32, f32) {
+        self.0.reference_hex_grid()
+    }
+
+    /// The number of bytes the generator holds on the heap.
+    ///
+    /// For more details, see `BlueNoise::memory_usage`.
+    pub fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+    }
+
+    /// The number of checkpoints recorded so far.
+    pub fn checkpoints(&self) -> usize {
+        self.0.checkpoints()
+    }
+
+    /// Generates a single tile of noise and replicates it across a
+    /// `cols` by `rows` grid of tiles, offsetting each copy into its
+    /// cell. Since the tile wraps, the combined set has no seams.
+    ///
+    /// ```
+    /// use bluenoise::WrappingBlueNoise;
+    /// use rand_pcg::Pcg64Mcg;
+    ///
+    /// let noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(10.0, 10.0, 1.0, 10);
+    /// let points = noise.tile_grid(4, 2);
+    ///
+    /// assert!(points.iter().all(|p| p.x < 40.0 && p.y < 20.0));
+    /// ```
+    pub fn tile_grid(self, cols: usize, rows: usize) -> Vec<Vec2> {
+        let size = Vec2::new(self.0.width, self.0.height);
+        let tile = self.collect::<Vec<_>>();
+
+        let mut points = Vec::with_capacity(tile.len() * cols * rows);
+        for (x, y) in (0..cols).cartesian_product(0..rows) {
+            let offset = size * Vec2::new(x as f32, y as f32);
+            points.extend(tile.iter().map(|point| *point + offset));
+        }
+        points
+    }
+
+    /// Compute the distance between two points
+    fn distance(&self, point: Vec2, target: Vec2) -> f32 {
+        let diff = {
+            let tmp = (target - point).abs();
+            tmp.min(Vec2::new(self.0.width, self.0.height) - tmp)
+        };
+        diff.length_squared()
+    }
+
+    /// Get the cells within reach of `cell`, wrapping around the grid.
+    ///
+    /// The last cell in each direction is usually only partially inside
+    /// the box, so when the range crosses the seam it is extended by one
+    /// cell to make sure nothing within the radius is missed.
+    fn wrapped_range(cell: isize, len: usize, reach: usize) -> impl Iterator<Item = usize> + Clone {
+        let len = len as isize;
+        let reach = reach as isize;
+        let start = if cell - reach < 0 {
+            cell - reach - 1
+        } else {
+            cell - reach
+        };
+        let end = if cell + reach + 1 >= len {
+            cell + reach + 2
+        } else {
+            cell + reach + 1
+        };
+        (start..end).map(move |x| x.rem_euclid(len) as usize)
+    }
+
+    /// Check if a position is far enough away from
+    /// nearby previously created points.
+    fn is_valid(&self, point: Vec2) -> bool {
+        // make sure the reflection has room too
+        if let Some(mirror) = self.0.mirror(point) {
+            let radius = self.0.radius_at(point);
+            if !self.0.in_half(point)
+                || (mirror != point
+                    && (self.distance(point, mirror) < self.0.min_distance_squared(radius, mirror)
+                        || !self.is_clear(mirror)))
+            {
+                return false;
+            }
+        }
+
+        self.is_clear(point)
+    }
+
+    /// Check that a position is clear of obstacles, and that every
+    /// nearby point is further than our min radius.
+    fn is_clear(&self, point: Vec2) -> bool {
+        if !self.0.is_allowed(point) {
+            return false;
+        }
+
+        let radius = self.0.radius_at(point);
+        self.neighbours(point, self.0.reach(radius)).all(|target| {
+            self.distance(point, target) >= self.0.min_distance_squared(radius, target)
+        })
+    }
+
+    /// Get the points in the grid within `reach` cells of a
+    /// position, wrapping around the edges.
+    fn neighbours(&self, point: Vec2, reach: usize) -> impl Iterator<Item = Vec2> + '_ {
+        let x_range = {
+            let x = (point.x / self.0.cell_size) as isize;
+            Self::wrapped_range(x, self.0.grid_width, reach)
+        };
+        let y_range = {
+            let y = (point.y / self.0.cell_size) as isize;
+            Self::wrapped_range(y, self.0.grid_height, reach)
+        };
+
+        x_range
+            .cartesian_product(y_range)
+            .filter_map(move |(x, y)| {
+                *self
+                    .0
+                    .grid
+                    .get(y * self.0.grid_width + x)
+                    .expect("Ended up out of bounds when fetching point.")
+            })
+    }
+
+    /// Check if a point has no neighbours within some distance.
+    fn is_isolated(&self, point: Vec2, max_spacing: f32) -> bool {
+        let reach = (max_spacing / self.0.cell_size).ceil() as usize;
+        self.neighbours(point, reach)
+            .all(|target| target == point || self.distance(point, target) > max_spacing.powi(2))
+    }
+
+    /// Get some point in the spacing band around a position
+    fn get_banded(&mut self, position: Vec2, max_spacing: f32) -> Vec2 {
+        let banded = self.0.get_banded(position, max_spacing);
+        Vec2::new(
+            banded.x.rem_euclid(self.0.width),
+            banded.y.rem_euclid(self.0.height),
+        )
+    }
+
+    /// Get some nearby point
+    fn get_nearby(&mut self, position: Vec2, seed: f32, sample: u32) -> Vec2 {
+        let nearby = self.0.get_nearby(position, seed, sample);
+        Vec2::new(
+            nearby.x.rem_euclid(self.0.width),
+            nearby.y.rem_euclid(self.0.height),
+        )
+    }
+}
+
+impl<R: Rng> WrappingBlueNoise<R> {
+    /// Generate the next point, without polling the stop condition.
+    fn next_point(&mut self) -> Option<Vec2> {
+        if let Some(point) = self.0.pending.pop() {
+            return Some(point);
+        }
+
+        if !self.0.init {
+            self.0.init = true;
+            for _ in 0..INITIAL_ATTEMPTS {
+                // a point too close to the axis is moved onto it
+                let mut point = self.0.initial_point();
+                if !self.is_valid(point) {
+                    point = self.0.snap_to_axis(point);
+                }
+                if self.is_valid(point) {
+                    return Some(self.0.insert_point(point));
+                }
+            }
+
+            // most of the box is blocked, so search the cells in order
+            let start = self
+                .0
+                .start_candidates()
+                .map(|point| {
+                    if self.is_valid(point) {
+                        point
+                    } else {
+                        self.0.snap_to_axis(point)
+                    }
+                })
+                .find(|point| self.is_valid(*point));
+            return start.map(|point| self.0.insert_point(point));
+        }
+
+        while !self.0.active_points.is_empty() {
+            let index = self.0.rng.gen_range(0..self.0.active_points.len());
+            let parent = self.0.active_points[index];
+
+            let seed = self.0.parent_offset(parent);
+            for sample in 0..self.0.samples() {
+                let point = self.get_nearby(parent, seed, sample);
+                if self.is_valid(point) {
+                    return Some(self.0.insert_point(point));
+                }
+            }
+
+            self.0.active_points.swap_remove(index);
+        }
+
+        // once the area is full, fill in any gaps that are too wide
+        if let Some(max_spacing) = self.0.max_spacing {
+            while let Some(cell) = self.0.grid.get(self.0.band_cursor) {
+                if let Some(parent) = *cell {
+                    if self.is_isolated(parent, max_spacing) {
+                        for _ in 0..self.0.max_samples {
+                            let point = self.get_banded(parent, max_spacing);
+                            if self.is_valid(point) {
+                                return Some(self.0.insert_point(point));
+                            }
+                        }
+                    }
+                }
+                self.0.band_cursor += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Evaluate the next candidate. This mirrors `next_point`, but
+    /// keeps track of where it is in the step between calls.
+    fn next_detail(&mut self, state: &mut DetailState) -> Option<StepDetail> {
+        if let Some(point) = self.0.pending.pop() {
+            return Some(StepDetail::Candidate {
+                point,
+                accepted: true,
+                parent: None,
+            });
+        }
+
+        if !self.0.init {
+            return self.next_point().map(|point| StepDetail::Candidate {
+                point,
+                accepted: true,
+                parent: None,
+            });
+        }
+
+        if state.attempt.is_none() && !self.0.active_points.is_empty() {
+            let index = self.0.rng.gen_range(0..self.0.active_points.len());
+            let parent = self.0.active_points[index];
+            let seed = self.0.parent_offset(parent);
+            state.attempt = Some((index, parent, seed, 0));
+        }
+
+        if let Some((index, parent, seed, sample)) = state.attempt {
+            if sample >= self.0.samples() {
+                state.attempt = None;
+                let point = self.0.active_points.swap_remove(index);
+                return Some(StepDetail::Retired { point });
+            }
+
+            let point = self.get_nearby(parent, seed, sample);
+            let accepted = self.is_valid(point);
+            state.attempt = if accepted {
+                self.0.insert_point(point);
+                None
+            } else {
+                Some((index, parent, seed, sample + 1))
+            };
+            return Some(StepDetail::Candidate {
+                point,
+                accepted,
+                parent: Some(parent),
+            });
+        }
+
+        if let Some(max_spacing) = self.0.max_spacing {
+            while let Some(cell) = self.0.grid.get(self.0.band_cursor) {
+                if let Some(parent) = *cell {
+                    if state.band_sample < self.0.max_samples
+                        && self.is_isolated(parent, max_spacing)
+                    {
+                        let point = self.get_banded(parent, max_spacing);
+                        let accepted = self.is_valid(point);
+                        if accepted {
+                            self.0.insert_point(point);
+                            state.band_sample = 0;
+                        } else {
+                            state.band_sample += 1;
+                        }
+                        return Some(StepDetail::Candidate {
+                            point,
+                            accepted,
+                            parent: Some(parent),
+                        });
+                    }
+                }
+                state.band_sample = 0;
+                self.0.band_cursor += 1;
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: Rng> Iterator for WrappingBlueNoise<R> {
+    type Item = Vec2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.should_stop() {
+            return None;
+        }
+
+        self.0.record_checkpoint();
+        let point = self.next_point();
+        self.0.yielded += usize::from(point.is_some());
+        point
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Axis, BlueNoise, MockClock, StepDetail, WrappingBlueNoise};
+    use glam::Vec2;
+    use itertools::Itertools;
+    use rand::{RngCore, SeedableRng};
+    use rand_pcg::Pcg64Mcg;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn get_points() {
+        let noise = BlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 1.0);
+        assert!(noise.count() > 0);
+    }
+
+    #[test]
+    fn get_points_gt_one() {
+        let noise = BlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 10.0);
+        assert!(noise.count() > 1);
+    }
+
+    #[test]
+    fn get_points_wrapping() {
+        let noise = WrappingBlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 1.0);
+        assert!(noise.count() > 0);
+    }
+
+    #[test]
+    fn get_points_wrapping_gt_one() {
+        let noise = WrappingBlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 10.0);
+        assert!(noise.count() > 1);
+    }
+
+    #[test]
+    fn generate_until_honours_budget() {
+        // every read of the clock advances it by 1ms, so after the
+        // initial read we get 9 checks that fall inside the budget
+        let clock = MockClock::new(Duration::from_millis(1));
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let points = noise.generate_until_with_clock(Duration::from_millis(10), &clock);
+        assert_eq!(points.len(), 9);
+
+        // and generation picks up where it left off
+        let expected = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10)
+            .take(10)
+            .collect::<Vec<_>>();
+        assert_eq!(points[..], expected[..9]);
+        assert_eq!(noise.next(), Some(expected[9]));
+    }
+
+    #[test]
+    fn generate_until_wrapping_honours_budget() {
+        let clock = MockClock::new(Duration::from_millis(1));
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let points = noise.generate_until_with_clock(Duration::from_millis(10), &clock);
+        assert_eq!(points.len(), 9);
+    }
+
+    #[test]
+    fn generate_until_stops_when_exhausted() {
+        let clock = MockClock::new(Duration::from_millis(0));
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(10.0, 10.0, 1.0, 10);
+        let points = noise.generate_until_with_clock(Duration::from_millis(1), &clock);
+        let expected = BlueNoise::<Pcg64Mcg>::from_seed(10.0, 10.0, 1.0, 10).count();
+        assert_eq!(points.len(), expected);
+    }
+
+    #[test]
+    fn tile_grid_has_no_seams() {
+        let radius = 2.0;
+        let noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(20.0, 20.0, radius, 10);
+        let tile_len = noise.clone().count();
+        let points = noise.tile_grid(3, 2);
+        assert_eq!(points.len(), tile_len * 6);
+
+        for (i, a) in points.iter().enumerate() {
+            assert!(a.x >= 0.0 && a.x < 60.0 && a.y >= 0.0 && a.y < 40.0);
+            for b in &points[i + 1..] {
+                assert!(a.distance(*b) >= radius - 1e-4, "{} too close to {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn size_hint_bounds_collect() {
+        let configs: Vec<fn(&mut BlueNoise<Pcg64Mcg>)> = vec![
+            |_| {},
+            |noise| {
+                noise.with_samples(1);
+            },
+            |noise| {
+                noise.with_fixed_angles(1);
+            },
+            |noise| {
+                noise.with_mirror_symmetry(Axis::Vertical);
+            },
+            |noise| {
+                noise.with_density(1.0, |point| 1.0 + point.x / 10.0);
+            },
+            |noise| {
+                noise.with_distance_transform(vec![0.0; 4], 2, 2, 1.0);
+            },
+            |noise| {
+                noise.with_exclusion_circles(vec![(Vec2::new(50.0, 50.0), 100.0)]);
+            },
+            |noise| {
+                noise.with_min_radius(2.0);
+            },
+            |noise| {
+                // seeds just too far apart to fit anything between
+                let seeds = (0..53 * 53).map(|i| Vec2::new((i % 53) as f32, (i / 53) as f32) * 1.9);
+                noise.insert_seed_points(seeds);
+            },
+        ];
+
+        for (width, height) in &[(100.0, 100.0), (100.0, 3.0), (3.0, 100.0)] {
+            for seed in 0..3 {
+                for configure in &configs {
+                    let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(*width, *height, 1.0, seed);
+                    configure(&mut noise);
+                    let (lower, upper) = noise.size_hint();
+                    let count = noise.collect::<Vec<_>>().len();
+                    assert!(lower <= count && count <= upper.unwrap());
+
+                    let mut noise =
+                        WrappingBlueNoise::<Pcg64Mcg>::from_seed(*width, *height, 1.0, seed);
+                    configure(&mut noise.0);
+                    let (lower, upper) = noise.size_hint();
+                    let count = noise.collect::<Vec<_>>().len();
+                    assert!(lower <= count && count <= upper.unwrap());
+                }
+            }
+        }
+
+        // a hexagonal covering of seeds, which leaves almost no room
+        let seeds = (0..60 * 70)
+            .map(|i| {
+                let (x, y) = ((i % 60) as f32, (i / 60) as f32);
+                let shift = if i / 60 % 2 == 1 { 0.5 } else { 0.0 };
+                Vec2::new(x + shift, y * 3f32.sqrt() / 2.0) * 1.72
+            })
+            .collect::<Vec<_>>();
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        assert!(noise.insert_seed_points(seeds) > 1000);
+        let (lower, upper) = noise.size_hint();
+        let count = noise.collect::<Vec<_>>().len();
+        assert!(lower <= count && count <= upper.unwrap());
+    }
+
+    #[test]
+    fn size_hint_tracks_progress() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let (lower, upper) = noise.size_hint();
+        assert_eq!(lower, noise.estimated_points());
+
+        noise.by_ref().take(100).for_each(drop);
+        assert_eq!(noise.size_hint(), (lower - 100, upper.map(|u| u - 100)));
+
+        noise.by_ref().for_each(drop);
+        assert_eq!(noise.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn density_varies_radius() {
+        let density = |point: Vec2| if point.x < 50.0 { 1.0 } else { 3.0 };
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let points = noise.with_density(1.0, density).collect::<Vec<_>>();
+
+        let left = points.iter().filter(|p| p.x < 50.0).count();
+        let right = points.len() - left;
+        assert!(left > right * 4);
+
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                let radius = density(*a).max(density(*b));
+                assert!(a.distance(*b) >= radius - 1e-4, "{} too close to {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn density_is_deterministic() {
+        let density = |point: Vec2| 1.0 + point.y / 20.0;
+        let mut a = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let mut b = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        assert_eq!(
+            a.with_density(1.0, density).collect::<Vec<_>>(),
+            b.with_density(1.0, density).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn constant_density_matches_plain() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let plain = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        assert_eq!(
+            noise.with_density(2.0, |_| 2.0).collect::<Vec<_>>(),
+            plain.collect::<Vec<_>>()
+        );
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let plain = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        assert_eq!(
+            noise.with_density(2.0, |_| 2.0).collect::<Vec<_>>(),
+            plain.collect::<Vec<_>>()
+        );
+    }
+
+    /// Seed points far more than `max` apart, which nothing grows from.
+    fn isolated_seeds() -> Vec<Vec2> {
+        (0..25)
+            .map(|i| Vec2::new((i % 5) as f32 * 10.0 + 5.0, (i / 5) as f32 * 10.0 + 5.0))
+            .collect()
+    }
+
+    #[test]
+    fn spacing_band_limits_gaps() {
+        let (min, max) = (1.0, 1.5);
+        let seeds = isolated_seeds();
+        let distance = |a: Vec2, b: Vec2| a.distance(b);
+
+        // without the band, the isolated seeds are left alone
+        let mut plain = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, min, 10);
+        plain.insert_seed_points(seeds.clone());
+        plain.active_points.clear();
+        assert_eq!(plain.count(), 0);
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, min, 10);
+        noise.with_spacing_band(min, max);
+        noise.insert_seed_points(seeds.clone());
+        noise.active_points.clear();
+        let mut points = noise.collect::<Vec<_>>();
+        assert!(points.len() > 1000);
+
+        for point in &points {
+            assert!(nearest_distance(*point, &points, distance) >= min);
+        }
+        points.extend(seeds.iter().copied());
+        for seed in &seeds {
+            assert!(nearest_distance(*seed, &points, distance) <= max);
+        }
+    }
+
+    #[test]
+    fn spacing_band_wrapping_limits_gaps() {
+        let (min, max) = (1.0, 1.5);
+        let size = Vec2::new(50.0, 50.0);
+        let seeds = isolated_seeds();
+        let wrapped = |a: Vec2, b: Vec2| {
+            let diff = (a - b).abs();
+            diff.min(size - diff).length()
+        };
+
+        let mut plain = WrappingBlueNoise::<Pcg64Mcg>::from_seed(size.x, size.y, min, 10);
+        plain.insert_seed_points(seeds.clone());
+        plain.0.active_points.clear();
+        assert_eq!(plain.count(), 0);
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(size.x, size.y, min, 10);
+        noise.with_spacing_band(min, max);
+        noise.insert_seed_points(seeds.clone());
+        noise.0.active_points.clear();
+        let mut points = noise.collect::<Vec<_>>();
+        assert!(points.len() > 1000);
+
+        for point in &points {
+            assert!(nearest_distance(*point, &points, wrapped) >= min);
+        }
+        points.extend(seeds.iter().copied());
+        for seed in &seeds {
+            assert!(nearest_distance(*seed, &points, wrapped) <= max);
+        }
+    }
+
+    /// Get the distance from a point to its nearest neighbour
+    fn nearest_distance(point: Vec2, points: &[Vec2], distance: impl Fn(Vec2, Vec2) -> f32) -> f32 {
+        points
+            .iter()
+            .filter(|other| **other != point)
+            .map(|other| distance(point, *other))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn with_candidates_accepts_valid_points() {
+        let candidates = vec![
+            Vec2::new(10.0, 10.0),
+            Vec2::new(10.5, 10.0),
+            Vec2::new(-5.0, 10.0),
+            Vec2::new(40.0, 40.0),
+            Vec2::new(50.0, 20.0),
+        ];
+
+        let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let points = noise
+            .with_candidates(candidates.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(points[..2], [candidates[0], candidates[3]]);
+        for skipped in &[candidates[1], candidates[2], candidates[4]] {
+            assert!(!points.contains(skipped));
+        }
+        assert!(points.len() > 2);
+
+        let noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let points = noise
+            .with_candidates(candidates.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(points[..2], [candidates[0], candidates[3]]);
+        for skipped in &[candidates[1], candidates[2], candidates[4]] {
+            assert!(!points.contains(skipped));
+        }
+    }
+
+    #[test]
+    fn seed_points_are_respected() {
+        let anchors = vec![
+            Vec2::new(10.0, 10.0),
+            Vec2::new(40.0, 25.0),
+            Vec2::new(41.0, 25.0),
+            Vec2::new(60.0, 25.0),
+        ];
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        assert_eq!(noise.insert_seed_points(anchors.clone()), 2);
+        let points = noise.collect::<Vec<_>>();
+        assert!(points.len() > 100);
+        for point in &points {
+            assert!(point.distance(anchors[0]) >= 2.0);
+            assert!(point.distance(anchors[1]) >= 2.0);
+        }
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        assert_eq!(noise.insert_seed_points(anchors.clone()), 2);
+        let points = noise.collect::<Vec<_>>();
+        assert!(points.len() > 100);
+        for point in &points {
+            assert!(point.distance(anchors[0]) >= 2.0);
+            assert!(point.distance(anchors[1]) >= 2.0);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.by_ref().take(50).for_each(drop);
+
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let resumed = serde_json::from_str::<BlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        assert_eq!(
+            resumed.take(50).collect::<Vec<_>>(),
+            noise.take(50).collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_wrapping() {
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.by_ref().take(50).for_each(drop);
+
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let resumed = serde_json::from_str::<WrappingBlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        assert_eq!(
+            resumed.take(50).collect::<Vec<_>>(),
+            noise.take(50).collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_checkpoints() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.with_checkpoints(50);
+        let first = noise.by_ref().take(120).collect::<Vec<_>>();
+
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let mut resumed = serde_json::from_str::<BlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        resumed.with_checkpoints(50);
+        assert_eq!(resumed.checkpoints(), 3);
+
+        // rewinding to a checkpoint from before the save replays it
+        resumed.rewind_to(1);
+        let replay = resumed.by_ref().take(150).collect::<Vec<_>>();
+        assert_eq!(first[50..], replay[..70]);
+        assert_eq!(replay[70..], noise.take(80).collect::<Vec<_>>()[..]);
+        assert_eq!(resumed.checkpoints(), 4);
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.with_checkpoints(50);
+        let first = noise.by_ref().take(120).collect::<Vec<_>>();
+
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let mut resumed = serde_json::from_str::<WrappingBlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        resumed.with_checkpoints(50).rewind_to(2);
+        assert_eq!(first[100..], resumed.take(20).collect::<Vec<_>>()[..]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_density() {
+        let density = |point: Vec2| 1.0 + point.x / 20.0;
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.with_density(1.0, density);
+        noise.by_ref().take(50).for_each(drop);
+
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let mut resumed = serde_json::from_str::<BlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        resumed.set_density(density);
+        assert_eq!(resumed.collect::<Vec<_>>(), noise.collect::<Vec<_>>());
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.with_density(1.0, density);
+        noise.by_ref().take(50).for_each(drop);
 
-        out
+        let snapshot = serde_json::to_string(&noise).unwrap();
+        let mut resumed = serde_json::from_str::<WrappingBlueNoise<Pcg64Mcg>>(&snapshot).unwrap();
+        resumed.set_density(density);
+        assert_eq!(resumed.collect::<Vec<_>>(), noise.collect::<Vec<_>>());
     }
 
-    /// Insert a point into the grid and mark it active
-    fn insert_point(&mut self, position: Vec2) -> Vec2 {
-        let index = self.grid_index(position);
-        self.grid[index] = Some(position);
-        self.active_points.push(position);
-        position
-    }
-}
+    #[test]
+    fn rng_draws_scale_with_points() {
+        let draws =
+            |width, radius| BlueNoise::<Pcg64Mcg>::new(width, width, radius).estimate_rng_draws();
+        let small = draws(50.0, 1.0);
+        assert!((3.5..4.5).contains(&(draws(100.0, 1.0) as f32 / small as f32)));
+        assert!((3.5..4.5).contains(&(draws(100.0, 2.0) as f32 / draws(50.0, 2.0) as f32)));
+        assert!((3.5..4.5).contains(&(small as f32 / draws(50.0, 2.0) as f32)));
 
-impl<R: Rng> Iterator for BlueNoise<R> {
-    type Item = Vec2;
+        let wrapping = WrappingBlueNoise::<Pcg64Mcg>::new(50.0, 50.0, 1.0).estimate_rng_draws();
+        assert_eq!(wrapping, small);
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.init {
-            self.init = true;
-            let x = self.rng.gen_range(0.0..self.width);
-            let y = self.rng.gen_range(0.0..self.height);
-            return Some(self.insert_point(Vec2::new(x, y)));
+    #[test]
+    fn rng_draws_bound_actual_draws() {
+        /// An rng that counts how many values are drawn from it
+        struct Counting(Pcg64Mcg, usize);
+        impl RngCore for Counting {
+            fn next_u32(&mut self) -> u32 {
+                self.1 += 1;
+                self.0.next_u32()
+            }
+            fn next_u64(&mut self) -> u64 {
+                self.1 += 2;
+                self.0.next_u64()
+            }
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.1 += dest.len().div_ceil(4);
+                self.0.fill_bytes(dest)
+            }
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
         }
 
-        while !self.active_points.is_empty() {
-            let index = self.rng.gen::<f32>() * (self.active_points.len() - 1) as f32;
-            let parent = self.active_points[index as usize];
+        let rng = Counting(Pcg64Mcg::seed_from_u64(10), 0);
+        let mut noise = BlueNoise::from_rng(50.0, 50.0, 1.0, rng);
+        let estimate = noise.estimate_rng_draws();
+        noise.by_ref().for_each(drop);
+        assert!(noise.rng.1 <= estimate);
+    }
 
-            let seed = self.rng.gen::<f32>();
-            for sample in 0..self.max_samples {
-                let point = self.get_nearby(parent, seed, sample);
-                if self.is_valid(point) {
-                    return Some(self.insert_point(point));
+    #[test]
+    fn mirror_symmetry_is_exact() {
+        for axis in &[Axis::Vertical, Axis::Horizontal] {
+            let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(40.0, 30.0, 2.0, 10);
+            let points = noise.with_mirror_symmetry(*axis).collect::<Vec<_>>();
+            let reflect = |p: Vec2| match axis {
+                Axis::Vertical => Vec2::new(40.0 - p.x, p.y),
+                Axis::Horizontal => Vec2::new(p.x, 30.0 - p.y),
+            };
+
+            for (i, a) in points.iter().enumerate() {
+                let mirror = reflect(*a);
+                assert!(
+                    points.iter().any(|b| b.distance(mirror) < 1e-4),
+                    "{} has no mirror",
+                    a
+                );
+                for b in &points[i + 1..] {
+                    assert!(a.distance(*b) >= 2.0 - 1e-4, "{} too close to {}", a, b);
                 }
             }
+        }
+    }
+
+    #[test]
+    fn mirror_symmetry_wrapping() {
+        let size = Vec2::new(40.0, 30.0);
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(size.x, size.y, 2.0, 10);
+        let points = noise
+            .with_mirror_symmetry(Axis::Vertical)
+            .collect::<Vec<_>>();
 
-            self.active_points.remove(index as usize);
+        for (i, a) in points.iter().enumerate() {
+            let mirror = Vec2::new(size.x - a.x, a.y);
+            assert!(
+                points.iter().any(|b| b.distance(mirror) < 1e-4),
+                "{} has no mirror",
+                a
+            );
+            for b in &points[i + 1..] {
+                let diff = (*a - *b).abs();
+                assert!(diff.min(size - diff).length() >= 2.0 - 1e-4);
+            }
         }
+    }
 
-        None
+    #[test]
+    fn morph_matches_endpoints() {
+        let sorted = |mut points: Vec<Vec2>| {
+            points.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+            points
+        };
+
+        let a = sorted(BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 1).collect());
+        let b = sorted(BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 2).collect());
+        assert_eq!(
+            sorted(BlueNoise::<Pcg64Mcg>::morph(50.0, 50.0, 2.0, 1, 2, 0.0)),
+            a
+        );
+        assert_eq!(
+            sorted(BlueNoise::<Pcg64Mcg>::morph(50.0, 50.0, 2.0, 1, 2, 1.0)),
+            b
+        );
+
+        let halfway = BlueNoise::<Pcg64Mcg>::morph(50.0, 50.0, 2.0, 1, 2, 0.5);
+        assert!(halfway.len() >= a.len().min(b.len()));
+        assert!(halfway
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x < 50.0 && p.y >= 0.0 && p.y < 50.0));
     }
-}
 
-/// Provides a source of `WrappingBlueNoise` in a given area at some
-/// density, where the distance between two points wraps around the
-/// edges of the box. This can be used to generate tiling blue noise.
-#[derive(Debug, Clone)]
-pub struct WrappingBlueNoise<R: Rng>(BlueNoise<R>);
+    #[test]
+    fn oriented_rect_stays_inside() {
+        let center = Vec2::new(20.0, -5.0);
+        let half_extents = Vec2::new(15.0, 4.0);
+        let angle = 0.6f32;
+        let points = BlueNoise::oriented_rect(
+            center,
+            half_extents,
+            angle,
+            1.0,
+            Pcg64Mcg::seed_from_u64(10),
+        );
+        assert!(points.len() > 100);
 
-impl<R: Rng + SeedableRng> WrappingBlueNoise<R> {
-    /// Creates a new instance of `WrappingBlueNoise`.
-    ///
-    /// * `width`: The width of the box to generate inside.
-    /// * `height`: The height of the box to generate inside.
-    /// * `min_radius`: The minimum distance between points.
-    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
-    pub fn new(width: f32, height: f32, min_radius: f32) -> Self {
-        Self(BlueNoise::new(width, height, min_radius))
+        let (sin, cos) = angle.sin_cos();
+        for point in points {
+            let offset = point - center;
+            let local = Vec2::new(
+                offset.x * cos + offset.y * sin,
+                -offset.x * sin + offset.y * cos,
+            );
+            assert!(
+                local.x.abs() <= half_extents.x + 1e-3,
+                "{} is outside",
+                point
+            );
+            assert!(
+                local.y.abs() <= half_extents.y + 1e-3,
+                "{} is outside",
+                point
+            );
+        }
     }
 
-    /// Creates a new instance of `WrappingBlueNoise`.
-    ///
-    /// * `width`: The width of the box to generate inside.
-    /// * `height`: The height of the box to generate inside.
-    /// * `min_radius`: The minimum distance between points.
-    /// * `seed`: Value to seed the rng with
-    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
-    pub fn from_seed(width: f32, height: f32, min_radius: f32, seed: u64) -> Self {
-        Self(BlueNoise::from_seed(width, height, min_radius, seed))
+    #[test]
+    fn stop_condition_stops_promptly() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let condition = {
+            let stop = Arc::clone(&stop);
+            move || stop.load(Ordering::SeqCst)
+        };
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let noise = noise.with_stop_condition(condition.clone());
+        assert_eq!(noise.take(20).count(), 20);
+        stop.store(true, Ordering::SeqCst);
+        assert_eq!(noise.next(), None);
+
+        // and carries on once the condition is cleared
+        stop.store(false, Ordering::SeqCst);
+        assert!(noise.next().is_some());
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let noise = noise.with_stop_condition(condition);
+        assert_eq!(noise.take(20).count(), 20);
+        stop.store(true, Ordering::SeqCst);
+        assert_eq!(noise.next(), None);
     }
 
-    /// A builder function to seed the rng with a specific
-    /// value.
-    ///
-    /// For an example, see the `WrappingBlueNoise` examples.
-    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
-        self.0.with_seed(seed);
-        self
+    #[test]
+    fn uniform_fills_count() {
+        let points = BlueNoise::uniform(30.0, 20.0, 1234, Pcg64Mcg::seed_from_u64(10));
+        assert_eq!(points.len(), 1234);
+        assert!(points
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x < 30.0 && p.y >= 0.0 && p.y < 20.0));
     }
-}
 
-impl<R: Rng> WrappingBlueNoise<R> {
-    /// Creates a new instance of `WrappingBlueNoise`.
-    ///
-    /// * `width`: The width of the box to generate inside.
-    /// * `height`: The height of the box to generate inside.
-    /// * `min_radius`: The minimum distance between points.
-    /// * `rng`: Rng to use
-    #[must_use = "This is quite expensive to initialise. You can iterate over it to consume it."]
-    pub fn from_rng(width: f32, height: f32, min_radius: f32, rng: R) -> Self {
-        Self(BlueNoise::from_rng(width, height, min_radius, rng))
+    #[test]
+    fn largest_empty_circle_of_saturated_set() {
+        let radius = 2.0;
+        let bounds = Vec2::new(40.0, 40.0);
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(bounds.x, bounds.y, radius, 10);
+        let noise = noise.with_samples(32);
+        let points = noise.clone().collect::<Vec<_>>();
+        let (center, dispersion) = noise.largest_empty_circle(&points, bounds);
+
+        // nothing can beat a hexagonal packing, and a saturated set has
+        // no gap much wider than the radius
+        assert!(dispersion >= radius / 3f32.sqrt());
+        assert!(dispersion <= radius * 1.1);
+
+        let nearest = points
+            .iter()
+            .map(|p| p.distance(center))
+            .fold(f32::INFINITY, f32::min);
+        assert!((nearest - dispersion).abs() < 1e-4);
     }
 
-    /// A builder function to set the maximum number of
-    /// samples to be when attempting to find new points.
-    ///
-    /// For an example, see the `WrappingBlueNoise` examples.
-    pub fn with_samples(&mut self, max_samples: u32) -> &mut Self {
-        self.0.with_samples(max_samples);
-        self
+    #[test]
+    fn largest_empty_circle_matches_brute_force() {
+        let bounds = Vec2::new(10.0, 10.0);
+        let noise = BlueNoise::<Pcg64Mcg>::from_seed(bounds.x, bounds.y, 1.0, 10);
+        let points = noise.clone().collect::<Vec<_>>();
+        let (_, dispersion) = noise.largest_empty_circle(&points, bounds);
+
+        let step = 0.02;
+        let brute = (0..=500)
+            .cartesian_product(0..=500)
+            .map(|(x, y)| Vec2::new(x as f32 * step, y as f32 * step))
+            .map(|c| {
+                points
+                    .iter()
+                    .map(|p| p.distance(c))
+                    .fold(f32::INFINITY, f32::min)
+            })
+            .fold(0.0, f32::max);
+        assert!(dispersion >= brute - 1e-4);
+        assert!(dispersion <= brute + step);
     }
 
-    /// A builder function to set the minimum radius between
-    /// points.
-    ///
-    /// For an example, see the `WrappingBlueNoise` examples.
-    pub fn with_min_radius(&mut self, min_radius: f32) -> &mut Self {
-        self.0.with_min_radius(min_radius);
-        self
+    #[test]
+    fn rewind_replays_exactly() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let noise = noise.with_checkpoints(100);
+        let first = noise.take(350).collect::<Vec<_>>();
+        assert_eq!(noise.checkpoints(), 4);
+
+        noise.rewind_to(2);
+        assert_eq!(noise.take(150).collect::<Vec<_>>(), first[200..]);
+
+        noise.rewind_to(0);
+        assert_eq!(noise.take(350).collect::<Vec<_>>(), first);
+        assert_eq!(noise.checkpoints(), 4);
+
+        // scrub forward again and carry on to the end
+        noise.rewind_to(3);
+        let rest = noise.collect::<Vec<_>>();
+        let expected = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10)
+            .skip(300)
+            .collect::<Vec<_>>();
+        assert_eq!(rest, expected);
     }
 
-    /// Resets the generator to begin creating noise from the beginning.
-    /// This will not reset the prng so if you want deterministic ordering,
-    /// make sure to set it explicitly.
-    ///
-    /// ```
-    /// use bluenoise::WrappingBlueNoise;
-    /// use rand_pcg::Pcg64Mcg;
-    ///
-    /// let mut noise = WrappingBlueNoise::<Pcg64Mcg>::new(10.0, 10.0, 1.0);
-    /// let first_10 = noise.with_seed(25).take(10).collect::<Vec<_>>();
-    ///
-    /// // make sure to re-initialise your seed!
-    /// noise.reset().with_seed(25);
-    /// let reset_10 = noise.take(10).collect::<Vec<_>>();
-    ///
-    /// assert_eq!(first_10, reset_10);
-    /// ```
-    pub fn reset(&mut self) -> &mut Self {
-        self.0.reset();
-        self
+    #[test]
+    fn checkpoints_enabled_late_skip_passed_milestones() {
+        let expected = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10).collect::<Vec<_>>();
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        noise.by_ref().take(250).for_each(drop);
+        noise.with_checkpoints(100);
+        assert_eq!(noise.checkpoints(), 0);
+
+        noise.by_ref().take(60).for_each(drop);
+        assert_eq!(noise.checkpoints(), 1);
+        noise.rewind_to(3);
+        assert_eq!(
+            noise.by_ref().take(50).collect::<Vec<_>>(),
+            expected[300..350]
+        );
+
+        // changing the interval after a rewind starts again from there
+        noise.rewind_to(3).with_checkpoints(50);
+        noise.by_ref().take(101).for_each(drop);
+        assert_eq!(noise.checkpoints(), 3);
+        noise.rewind_to(7);
+        assert_eq!(
+            noise.by_ref().take(50).collect::<Vec<_>>(),
+            expected[350..400]
+        );
+
+        // seed points aren't counted, so the first checkpoint is the start
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        noise.with_checkpoints(10);
+        noise.insert_seed_points((0..25).map(|i| Vec2::new(i as f32 * 2.0, 1.0)));
+        let first = noise.by_ref().take(30).collect::<Vec<_>>();
+        noise.rewind_to(0);
+        assert_eq!(noise.by_ref().take(30).collect::<Vec<_>>(), first);
+        noise.rewind_to(1);
+        assert_eq!(noise.by_ref().take(20).collect::<Vec<_>>(), first[10..]);
     }
 
-    /// Compute the distance between two points
-    fn distance(&self, point: Vec2, target: Vec2) -> f32 {
-        let diff = {
-            let tmp = (target - point).abs();
-            tmp.min(Vec2::new(self.0.width, self.0.height) - tmp)
-        };
-        diff.length_squared()
+    #[test]
+    fn rewind_replays_wrapping() {
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let noise = noise.with_checkpoints(100);
+        let first = noise.take(350).collect::<Vec<_>>();
+
+        noise.rewind_to(1);
+        assert_eq!(noise.take(250).collect::<Vec<_>>(), first[100..]);
     }
 
-    /// Check if a position is far enough away from
-    /// nearby previously created points.
-    fn is_valid(&self, point: Vec2) -> bool {
-        let x_range = {
-            let x = (point.x / self.0.cell_size) as isize;
-            ((x - 2)..(x + 3)).map(|x| x.rem_euclid(self.0.grid_width as isize) as usize)
+    #[test]
+    fn steps_detailed_accepts_generated_points() {
+        let accepted = |steps: &[StepDetail]| {
+            steps
+                .iter()
+                .filter_map(|step| match step {
+                    StepDetail::Candidate {
+                        point,
+                        accepted: true,
+                        ..
+                    } => Some(*point),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
         };
-        let y_range = {
-            let y = (point.y / self.0.cell_size) as isize;
-            ((y - 2)..(y + 3)).map(|y| y.rem_euclid(self.0.grid_height as isize) as usize)
+        let retired = |steps: &[StepDetail]| {
+            steps
+                .iter()
+                .filter(|step| matches!(step, StepDetail::Retired { .. }))
+                .count()
         };
 
-        x_range.cartesian_product(y_range).all(|(x, y)| {
-            // if there is a point, check if it is further than our min radius
-            match self
-                .0
-                .grid
-                .get(y * self.0.grid_width + x)
-                .expect("Ended up out of bounds when fetching point.")
+        let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let points = noise.clone().collect::<Vec<_>>();
+        let steps = noise.steps_detailed().collect::<Vec<_>>();
+        assert!(steps.len() > points.len() * 2);
+        assert_eq!(accepted(&steps), points);
+        assert_eq!(retired(&steps), points.len());
+
+        let noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let points = noise.clone().collect::<Vec<_>>();
+        let steps = noise.steps_detailed().collect::<Vec<_>>();
+        assert_eq!(accepted(&steps), points);
+        assert_eq!(retired(&steps), points.len());
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let noise = noise
+            .with_spacing_band(1.0, 1.2)
+            .with_mirror_symmetry(Axis::Vertical);
+        let points = noise.clone().collect::<Vec<_>>();
+        let steps = noise.clone().steps_detailed().collect::<Vec<_>>();
+        assert_eq!(accepted(&steps), points);
+    }
+
+    #[test]
+    fn fixed_angles_tries_k_angles() {
+        let k = 6;
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        let steps = noise.with_fixed_angles(k).clone().steps_detailed();
+
+        // collect the angles tried around each parent
+        let mut angles = std::collections::HashMap::<(u32, u32), Vec<f32>>::new();
+        for step in steps {
+            if let StepDetail::Candidate {
+                point,
+                parent: Some(parent),
+                ..
+            } = step
             {
-                Some(target) => self.distance(point, *target) >= self.0.radius_squared,
-                None => true,
+                let offset = point - parent;
+                let angle = offset
+                    .y
+                    .atan2(offset.x)
+                    .rem_euclid(2.0 * std::f32::consts::PI);
+                let key = (parent.x.to_bits(), parent.y.to_bits());
+                angles.entry(key).or_default().push(angle);
             }
-        })
-    }
+        }
 
-    /// Get some nearby point
-    fn get_nearby(&mut self, position: Vec2, seed: f32, sample: u32) -> Vec2 {
-        let nearby = self.0.get_nearby(position, seed, sample);
-        Vec2::new(
-            nearby.x.rem_euclid(self.0.width),
-            nearby.y.rem_euclid(self.0.height),
-        )
+        assert!(!angles.is_empty());
+        for tried in angles.values_mut() {
+            tried.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            tried.dedup_by(|a, b| (*a - *b).abs() < 1e-3);
+            assert_eq!(tried.len(), k as usize);
+            for pair in tried.windows(2) {
+                assert!((pair[1] - pair[0] - 2.0 * std::f32::consts::PI / k as f32).abs() < 1e-3);
+            }
+        }
     }
-}
 
-impl<R: Rng> Iterator for WrappingBlueNoise<R> {
-    type Item = Vec2;
+    #[test]
+    fn distance_transform_avoids_obstacles() {
+        // a ring of obstacles around a clear middle, with a wall down the middle
+        let (cols, rows) = (20, 20);
+        let clearance = (0..cols * rows)
+            .map(|i| {
+                let (x, y) = (i % cols, i / cols);
+                let edge = x.min(y).min(cols - 1 - x).min(rows - 1 - y);
+                let wall = (x as isize - 10).unsigned_abs();
+                edge.min(wall) as f32
+            })
+            .collect::<Vec<_>>();
+        let cell = |p: Vec2| clearance[(p.y / 2.5) as usize * cols + (p.x / 2.5) as usize];
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.0.init {
-            self.0.init = true;
-            let x = self.0.rng.gen_range(0.0..self.0.width);
-            let y = self.0.rng.gen_range(0.0..self.0.height);
-            return Some(self.0.insert_point(Vec2::new(x, y)));
+        for seed in 0..5 {
+            let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, seed);
+            let points = noise
+                .with_distance_transform(clearance.clone(), cols, rows, 2.0)
+                .collect::<Vec<_>>();
+            assert!(points.len() > 100);
+            assert!(points.iter().all(|p| cell(*p) >= 2.0));
+
+            let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, seed);
+            let points = noise
+                .with_distance_transform(clearance.clone(), cols, rows, 2.0)
+                .collect::<Vec<_>>();
+            assert!(points.len() > 100);
+            assert!(points.iter().all(|p| cell(*p) >= 2.0));
         }
+    }
 
-        while !self.0.active_points.is_empty() {
-            let index = self.0.rng.gen::<f32>() * (self.0.active_points.len() - 1) as f32;
-            let parent = self.0.active_points[index as usize];
+    #[test]
+    fn reference_hex_grid_matches_radius() {
+        for radius in &[0.5, 1.0, 3.0] {
+            let noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, *radius, 10);
+            let (spacing, row_offset) = noise.reference_hex_grid();
+            assert!((spacing - radius).abs() < 1e-6);
+            assert!((row_offset / spacing - 3f32.sqrt() / 2.0).abs() < 1e-6);
 
-            let seed = self.0.rng.gen::<f32>();
-            for sample in 0..self.0.max_samples {
-                let point = self.get_nearby(parent, seed, sample);
-                if self.is_valid(point) {
-                    return Some(self.0.insert_point(point));
-                }
+            // neighbours in adjacent rows are also exactly one spacing apart
+            let diagonal = Vec2::new(spacing / 2.0, row_offset).length();
+            assert!((diagonal - spacing).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn patchwork_small_patches_leave_no_gaps() {
+        let radius = 1.0;
+        for patch_size in &[1.0, 2.0, 3.5] {
+            let points = BlueNoise::<Pcg64Mcg>::patchwork(60.0, 60.0, radius, *patch_size, 10);
+            for (i, point) in points.iter().enumerate() {
+                let distance = nearest_distance(*point, &points[i + 1..], |a, b| a.distance(b));
+                assert!(
+                    distance >= radius,
+                    "{} is {} from a neighbour",
+                    point,
+                    distance
+                );
             }
 
-            self.0.active_points.remove(index as usize);
+            let noise = BlueNoise::<Pcg64Mcg>::from_seed(60.0, 60.0, radius, 10);
+            let (_, gap) = noise.largest_empty_circle(&points, Vec2::new(60.0, 60.0));
+            assert!(
+                gap < 1.7 * radius,
+                "gap of {} with patches of {}",
+                gap,
+                patch_size
+            );
         }
+    }
 
-        None
+    #[test]
+    fn patchwork_keeps_spacing_across_patches() {
+        let radius = 1.0;
+        let points = BlueNoise::<Pcg64Mcg>::patchwork(60.0, 40.0, radius, 10.0, 10);
+        assert!(points.len() > 1000);
+        assert!(points
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x < 60.0 && p.y >= 0.0 && p.y < 40.0));
+
+        for (i, point) in points.iter().enumerate() {
+            let distance = nearest_distance(*point, &points[i + 1..], |a, b| a.distance(b));
+            assert!(
+                distance >= radius,
+                "{} is {} from a neighbour",
+                point,
+                distance
+            );
+        }
+
+        // the patches grow into each other, so there are no gaps at the seams
+        let noise = BlueNoise::<Pcg64Mcg>::from_seed(60.0, 40.0, radius, 10);
+        let (_, gap) = noise.largest_empty_circle(&points, Vec2::new(60.0, 40.0));
+        assert!(gap < 2.0 * radius);
+
+        // each patch has its own seed
+        let other = BlueNoise::<Pcg64Mcg>::patchwork(60.0, 40.0, radius, 10.0, 11);
+        assert_ne!(points, other);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{BlueNoise, WrappingBlueNoise};
-    use rand_pcg::Pcg64Mcg;
+    #[test]
+    fn memory_usage_scales_with_grid() {
+        let slot = std::mem::size_of::<Option<Vec2>>();
+        let small = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        let wide = BlueNoise::<Pcg64Mcg>::from_seed(200.0, 100.0, 1.0, 10);
+        let large = BlueNoise::<Pcg64Mcg>::from_seed(200.0, 200.0, 1.0, 10);
+        for noise in &[&small, &wide, &large] {
+            assert_eq!(
+                noise.memory_usage(),
+                noise.grid_width * noise.grid_height * slot
+            );
+        }
+
+        // the grid rounds up to whole cells, so this is only roughly linear
+        let ratio = |a: &BlueNoise<Pcg64Mcg>| a.memory_usage() as f32 / small.memory_usage() as f32;
+        assert!((ratio(&wide) - 2.0).abs() < 0.02);
+        assert!((ratio(&large) - 4.0).abs() < 0.04);
+
+        // the active points grow as the generator runs
+        let mut noise = small.clone();
+        noise.by_ref().take(10).for_each(drop);
+        assert!(noise.memory_usage() > small.memory_usage());
+
+        // and every checkpoint holds another copy of the grid
+        let mut noise = small.clone();
+        noise.with_checkpoints(100);
+        noise.by_ref().take(301).for_each(drop);
+        assert_eq!(noise.checkpoints(), 4);
+        assert!(noise.memory_usage() > 5 * small.memory_usage());
+
+        let mut noise = small.clone();
+        noise.with_distance_transform(vec![1.0; 100], 10, 10, 1.0);
+        assert!(noise.memory_usage() >= small.memory_usage() + 100 * 4);
+        noise.with_exclusion_circles(vec![(Vec2::new(50.0, 50.0), 10.0)]);
+        assert!(noise.memory_usage() >= small.memory_usage() + 100 * 4 + 20 * 20 * 8);
+    }
 
     #[test]
-    fn get_points() {
-        let noise = BlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 1.0);
-        assert!(noise.count() > 0);
+    fn exclusion_circles_are_avoided() {
+        let mut rng = Pcg64Mcg::seed_from_u64(10);
+        let circles = (0..40)
+            .map(|_| {
+                let center = Vec2::new(rng.next_u32() as f32, rng.next_u32() as f32)
+                    / u32::MAX as f32
+                    * 100.0;
+                (center, 1.0 + (rng.next_u32() % 8) as f32)
+            })
+            .collect::<Vec<_>>();
+        let outside = |p: &Vec2| circles.iter().all(|(c, r)| p.distance(*c) >= *r);
+
+        for seed in 0..5 {
+            let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, seed);
+            let points = noise
+                .with_exclusion_circles(circles.clone())
+                .collect::<Vec<_>>();
+            assert!(points.len() > 1000);
+            assert!(points.iter().all(outside));
+
+            let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, seed);
+            let points = noise
+                .with_exclusion_circles(circles.clone())
+                .collect::<Vec<_>>();
+            assert!(points.len() > 1000);
+            assert!(points.iter().all(outside));
+        }
     }
 
     #[test]
-    fn get_points_gt_one() {
-        let noise = BlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 10.0);
-        assert!(noise.count() > 1);
+    fn exclusion_circles_index_ignores_large_circles() {
+        // many small circles, and one large one that would swamp a coarse index
+        let mut circles = (0..400)
+            .map(|i| (Vec2::new((i % 20) as f32 * 5.0, (i / 20) as f32 * 5.0), 0.5))
+            .collect::<Vec<_>>();
+        circles.push((Vec2::new(50.0, 50.0), 20.0));
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(100.0, 100.0, 1.0, 10);
+        noise.with_exclusion_circles(circles.clone());
+
+        let exclusions = noise.exclusions.as_ref().unwrap();
+        assert_eq!(exclusions.cell_size, 1.0);
+        assert!(exclusions.buckets.iter().all(|bucket| bucket.len() <= 5));
+
+        let points = noise.collect::<Vec<_>>();
+        assert!(points.len() > 1000);
+        assert!(points
+            .iter()
+            .all(|p| circles.iter().all(|(c, r)| p.distance(*c) >= *r)));
     }
 
     #[test]
-    fn get_points_wrapping() {
-        let noise = WrappingBlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 1.0);
-        assert!(noise.count() > 0);
+    fn distance_transform_finds_small_clear_region() {
+        // only the 2x2 cells in the corner are clear, a 5x5 region of the box
+        let (cols, rows) = (20, 20);
+        let clearance = (0..cols * rows)
+            .map(|i| {
+                if i % cols < 2 && i / cols < 2 {
+                    5.0
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let in_corner = |p: &Vec2| p.x < 5.0 && p.y < 5.0;
+
+        for seed in 0..100 {
+            let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, seed);
+            let points = noise
+                .with_distance_transform(clearance.clone(), cols, rows, 1.0)
+                .collect::<Vec<_>>();
+            assert!(!points.is_empty(), "seed {} gave no points", seed);
+            assert!(points.iter().all(in_corner));
+
+            let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, seed);
+            let points = noise
+                .with_distance_transform(clearance.clone(), cols, rows, 1.0)
+                .collect::<Vec<_>>();
+            assert!(!points.is_empty(), "seed {} gave no points", seed);
+            assert!(points.iter().all(in_corner));
+        }
+
+        // with nowhere clear, nothing is generated
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let noise = noise.with_distance_transform(vec![0.0; cols * rows], cols, rows, 1.0);
+        assert_eq!(noise.count(), 0);
     }
 
     #[test]
-    fn get_points_wrapping_gt_one() {
-        let noise = WrappingBlueNoise::<Pcg64Mcg>::new(100.0, 100.0, 10.0);
-        assert!(noise.count() > 1);
+    fn exclusion_circles_find_small_gap() {
+        // a ring of circles covering all but a small hole around the middle
+        let circles = vec![
+            (Vec2::new(0.0, 0.0), 34.0),
+            (Vec2::new(50.0, 0.0), 34.0),
+            (Vec2::new(0.0, 50.0), 34.0),
+            (Vec2::new(50.0, 50.0), 34.0),
+        ];
+        let outside = |p: &Vec2| circles.iter().all(|(c, r)| p.distance(*c) >= *r);
+
+        for seed in 0..20 {
+            let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, seed);
+            let points = noise
+                .with_exclusion_circles(circles.clone())
+                .collect::<Vec<_>>();
+            assert!(!points.is_empty(), "seed {} gave no points", seed);
+            assert!(points.iter().all(outside));
+        }
+    }
+
+    #[test]
+    fn stop_condition_is_shared_by_clones() {
+        let mut polls = 0;
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        noise.with_stop_condition(move || {
+            polls += 1;
+            polls > 10
+        });
+
+        assert_eq!(noise.clone().take(4).count(), 4);
+        assert_eq!(noise.count(), 6);
+    }
+
+    #[test]
+    fn mirror_symmetry_accepts_either_half() {
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        noise.with_mirror_symmetry(Axis::Vertical);
+        let seeds = vec![Vec2::new(40.0, 10.0), Vec2::new(25.5, 30.0)];
+        assert_eq!(noise.insert_seed_points(seeds), 1);
+        for point in noise {
+            assert!(point.distance(Vec2::new(40.0, 10.0)) >= 2.0);
+            assert!(point.distance(Vec2::new(10.0, 10.0)) >= 2.0);
+        }
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        noise.with_mirror_symmetry(Axis::Horizontal);
+        assert_eq!(noise.insert_seed_points(vec![Vec2::new(10.0, 40.0)]), 1);
+        for point in noise {
+            assert!(point.distance(Vec2::new(10.0, 40.0)) >= 2.0);
+            assert!(point.distance(Vec2::new(10.0, 10.0)) >= 2.0);
+        }
+
+        // the candidate comes first, then its reflection
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        noise.with_mirror_symmetry(Axis::Vertical);
+        let points = noise
+            .clone()
+            .with_candidates(vec![Vec2::new(40.0, 10.0)])
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![Vec2::new(40.0, 10.0), Vec2::new(10.0, 10.0)]);
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 2.0, 10);
+        noise.with_mirror_symmetry(Axis::Vertical);
+        let points = noise
+            .clone()
+            .with_candidates(vec![Vec2::new(40.0, 10.0)])
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![Vec2::new(40.0, 10.0), Vec2::new(10.0, 10.0)]);
+    }
+
+    #[test]
+    fn clearance_scaling_spreads_points_out() {
+        // clearance grows from 0 on the left edge to 19 on the right
+        let (cols, rows) = (20, 20);
+        let clearance = (0..cols * rows)
+            .map(|i| (i % cols) as f32)
+            .collect::<Vec<_>>();
+        let scale = 0.2;
+        let radius = |p: Vec2| (scale * clearance[(p.x / 2.5) as usize]).max(1.0);
+
+        let mut noise = BlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let points = noise
+            .with_distance_transform(clearance.clone(), cols, rows, 1.0)
+            .with_clearance_scaling(scale)
+            .collect::<Vec<_>>();
+
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                let min = radius(*a).max(radius(*b));
+                assert!(a.distance(*b) >= min - 1e-4, "{} too close to {}", a, b);
+            }
+        }
+
+        let left = points.iter().filter(|p| p.x < 25.0).count();
+        let right = points.len() - left;
+        assert!(left > right * 3);
+
+        let mut noise = WrappingBlueNoise::<Pcg64Mcg>::from_seed(50.0, 50.0, 1.0, 10);
+        let points = noise
+            .with_distance_transform(clearance.clone(), cols, rows, 1.0)
+            .with_clearance_scaling(scale)
+            .collect::<Vec<_>>();
+        let left = points.iter().filter(|p| p.x < 25.0).count();
+        assert!(left > (points.len() - left) * 3);
     }
 }